@@ -0,0 +1,76 @@
+//! Memory per search node on the ttac2021 levels.
+//!
+//! Run with `cargo bench --bench node_memory`.
+
+use solve_sokoban::solver::Solver;
+use solve_sokoban::{Input, Map};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+const LEVELS: [(&str, &str); 3] = [
+    ("ttac2021/level1", include_str!("../tests/ttac2021/level1.txt")),
+    ("ttac2021/level2", include_str!("../tests/ttac2021/level2.txt")),
+    ("ttac2021/level3", include_str!("../tests/ttac2021/level3.txt")),
+];
+
+/// Peak heap bytes and generated states of `Solver::new()` on `LEVELS` before the node arena,
+/// when every open state owned a clone of the map and of the pushes leading to it. Measured
+/// with `CountingAllocator` on the commit before the arena was introduced.
+const BEFORE: [(usize, usize); 3] = [(1_217_982, 23_479), (131_264, 2_094), (522_960, 6_210)];
+
+/// Tracks the current and the peak number of allocated heap bytes.
+struct CountingAllocator;
+
+static CURRENT: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let current = CURRENT.fetch_add(layout.size(), Ordering::Relaxed) + layout.size();
+        PEAK.fetch_max(current, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        CURRENT.fetch_sub(layout.size(), Ordering::Relaxed);
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+fn main() {
+    println!(
+        "{:<16} {:>10} {:>10} {:>12} {:>12} {:>10} {:>10} {:>10}",
+        "level",
+        "generated",
+        "(before)",
+        "peak [B]",
+        "(before)",
+        "[B/node]",
+        "(before)",
+        "estimate"
+    );
+    for ((name, level), (before_peak, before_generated)) in LEVELS.into_iter().zip(BEFORE) {
+        let map = Map::from(Input::from_str(level).unwrap());
+        let base = CURRENT.load(Ordering::Relaxed);
+        PEAK.store(base, Ordering::Relaxed);
+        let (solution, stats) = Solver::new().solve_with_stats(&map);
+        let peak = PEAK.load(Ordering::Relaxed) - base;
+        assert!(solution.is_some());
+
+        println!(
+            "{:<16} {:>10} {:>10} {:>12} {:>12} {:>10} {:>10} {:>10}",
+            name,
+            stats.generated,
+            before_generated,
+            peak,
+            before_peak,
+            peak / stats.generated.max(1),
+            before_peak / before_generated,
+            stats.memory_per_node(),
+        );
+    }
+}
@@ -0,0 +1,82 @@
+use crate::algos::dijkstra::Cost;
+use crate::{Map, Pos};
+
+const UNREACHABLE: Cost = Cost::MAX;
+
+/// Walking distances of the player to all squares of a map, computed with one breadth-first
+/// search. The buffers are reused between updates.
+pub struct DistanceMap {
+    dist: Vec<Cost>,
+    queue: Vec<usize>,
+}
+
+impl Default for DistanceMap {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DistanceMap {
+    pub fn new() -> Self {
+        Self {
+            dist: vec![],
+            queue: vec![],
+        }
+    }
+
+    /// Distances from the player of `map`.
+    pub fn from_player(map: &Map) -> Self {
+        let mut result = Self::new();
+        result.update(map);
+        result
+    }
+
+    /// Recomputes the distances from the player of `map`.
+    pub fn update(&mut self, map: &Map) {
+        self.dist.clear();
+        self.dist.resize(map.squares(), UNREACHABLE);
+        self.queue.clear();
+
+        let start = match map.square(map.player()) {
+            Some(start) => start,
+            None => return,
+        };
+        self.dist[start] = 0;
+        self.queue.push(start);
+
+        let offsets = map.offsets();
+        let mut i = 0;
+        while i < self.queue.len() {
+            let square = self.queue[i];
+            i += 1;
+
+            let cost = self.dist[square].saturating_add(1);
+            for offset in offsets {
+                let next = square.wrapping_add_signed(offset);
+                if self.dist[next] == UNREACHABLE && map.is_free_square(next) {
+                    self.dist[next] = cost;
+                    self.queue.push(next);
+                }
+            }
+        }
+    }
+
+    /// Walking distance to the square with index `square`, if it is reachable.
+    #[inline]
+    pub fn distance(&self, square: usize) -> Option<Cost> {
+        match self.dist.get(square) {
+            Some(&cost) if cost != UNREACHABLE => Some(cost),
+            _ => None,
+        }
+    }
+
+    /// Walking distance to `pos`, if it is reachable.
+    pub fn distance_to(&self, map: &Map, pos: Pos) -> Option<Cost> {
+        map.square(pos).and_then(|square| self.distance(square))
+    }
+
+    /// Indices of all reachable squares in the order of their distance.
+    pub fn reachable(&self) -> &[usize] {
+        &self.queue
+    }
+}
@@ -1,16 +1,15 @@
 use std::cmp::Ordering;
-use std::collections::{BinaryHeap, HashMap};
+use std::collections::BinaryHeap;
 use std::ops::{Index, IndexMut};
 
 use crate::{Map, Pos};
-use crate::algos::matrix::Matrix;
 
-pub type Cost = u16;
+pub type Cost = u32;
 
 #[derive(Copy, Clone)]
 struct State {
     cost: Cost,
-    position: Pos,
+    square: usize,
 }
 
 impl Eq for State {}
@@ -29,104 +28,92 @@ impl PartialEq<State> for State {
 
 impl PartialOrd for State {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        other.cost.partial_cmp(&self.cost)
+        Some(self.cmp(other))
     }
 }
 
 
-struct DistMap(Matrix<Cost>);
+struct DistMap(Vec<Cost>);
 
 impl DistMap {
     pub fn new(map: &Map) -> Self {
-        Self(Matrix::fill(u16::MAX, map.width(), map.height()))
+        Self(vec![Cost::MAX; map.squares()])
     }
 }
 
-impl IndexMut<Pos> for DistMap {
-    fn index_mut(&mut self, index: Pos) -> &mut Cost {
-        &mut self.0[(index.x as usize, index.y as usize)]
+impl IndexMut<usize> for DistMap {
+    fn index_mut(&mut self, square: usize) -> &mut Cost {
+        &mut self.0[square]
     }
 }
 
-impl Index<Pos> for DistMap {
+impl Index<usize> for DistMap {
     type Output = Cost;
 
-    fn index(&self, index: Pos) -> &Cost {
-        &self.0[(index.x as usize, index.y as usize)]
+    fn index(&self, square: usize) -> &Cost {
+        &self.0[square]
     }
 }
 
+/// Squares the player can step to from each square, indexed by the squares of a map.
 pub struct PathGraph {
-    edges: HashMap<Pos, Vec<Pos>>,
+    edges: Vec<Vec<usize>>,
 }
 
 impl PathGraph {
     pub fn new(map: &Map) -> Self {
-        let mut edges: HashMap<Pos, Vec<Pos>> =
-            HashMap::with_capacity((map.height() * map.width()) / 2);
-        for y in 0..map.height() {
-            for x in 0..map.width() {
-                let pos = Pos::new(x as u8, y as u8);
-                if !map.is_wall(pos) {
-                    edges.insert(pos, Self::calc_edges(map, pos));
+        let edges = (0..map.squares())
+            .map(|square| {
+                if map.is_wall_square(square) {
+                    return vec![];
                 }
-            }
-        }
+                map.offsets()
+                    .iter()
+                    .map(|offset| square.wrapping_add_signed(*offset))
+                    .filter(|next| map.is_free_square(*next))
+                    .collect()
+            })
+            .collect();
 
         Self { edges }
     }
 
-    fn calc_edges(map: &Map, pos: Pos) -> Vec<Pos> {
-        let mut res = Vec::with_capacity(4);
-        if map.is_free(pos.up()) {
-            res.push(pos.up());
-        }
-        if map.is_free(pos.down()) {
-            res.push(pos.down());
-        }
-        if map.is_free(pos.left()) {
-            res.push(pos.left());
-        }
-        if map.is_free(pos.right()) {
-            res.push(pos.right());
-        }
-        res
-    }
-
-    pub fn edges(&self, pos: Pos) -> &[Pos] {
-        &self.edges[&pos]
+    pub fn edges(&self, square: usize) -> &[usize] {
+        &self.edges[square]
     }
 }
 
 /// Dijkstra's shortest path algorithm.
-pub fn shortest_path(map: &Map, graph: &PathGraph, start: Pos, goal: Pos) -> Option<u16> {
+pub fn shortest_path(map: &Map, graph: &PathGraph, start: Pos, goal: Pos) -> Option<Cost> {
+    let start = map.square(start)?;
+    let goal = map.square(goal)?;
     let mut dist: DistMap = DistMap::new(map);
     let mut heap = BinaryHeap::new();
 
     dist[start] = 0;
     heap.push(State {
         cost: 0,
-        position: start,
+        square: start,
     });
 
-    while let Some(State { cost, position }) = heap.pop() {
-        if position == goal {
+    while let Some(State { cost, square }) = heap.pop() {
+        if square == goal {
             return Some(cost);
         }
 
-        if cost > dist[position] {
+        if cost > dist[square] {
             continue;
         }
 
-        for edge in graph.edges(position) {
+        for edge in graph.edges(square) {
             let next = State {
-                cost: cost + 1,
-                position: edge.clone(),
+                cost: cost.saturating_add(1),
+                square: *edge,
             };
 
-            if next.cost < dist[next.position] {
+            if next.cost < dist[next.square] {
                 heap.push(next);
-                dist[next.position] = next.cost;
+                dist[next.square] = next.cost;
             }
         }
     }
@@ -1,3 +1,5 @@
 pub mod astar;
+pub mod bfs;
 pub mod dijkstra;
 pub mod matrix;
+pub mod reachability;
@@ -0,0 +1,146 @@
+use crate::algos::dijkstra::Cost;
+use crate::bitboard::BitBoard;
+use crate::{Map, Move};
+
+/// Region the player can walk to, kept up to date while boxes are pushed.
+///
+/// After a push only the old and the new square of the box change. The region grows by the old
+/// square of the box and everything that is reachable through it. It only has to be recomputed
+/// from scratch when the new square of the box might cut the region into parts. Walking
+/// distances come from a breadth-first search which only runs until the requested square is
+/// reached and continues from there on the next request.
+pub struct Reachability {
+    region: BitBoard,
+    dist: Vec<Cost>,
+    /// Squares in the order of their distance, the first `expanded` ones have been visited.
+    queue: Vec<usize>,
+    expanded: usize,
+    stack: Vec<usize>,
+}
+
+impl Reachability {
+    pub fn new(map: &Map) -> Self {
+        Self {
+            region: map.reachable(),
+            dist: vec![],
+            queue: vec![],
+            expanded: 0,
+            stack: vec![],
+        }
+    }
+
+    /// Squares the player can walk to.
+    pub fn region(&self) -> &BitBoard {
+        &self.region
+    }
+
+    #[inline]
+    pub fn is_reachable(&self, square: usize) -> bool {
+        self.region.contains(square)
+    }
+
+    /// Updates the region after `m` was applied to `map`.
+    pub fn apply_move(&mut self, map: &Map, m: Move) {
+        self.invalidate_distances();
+
+        let start = map.square(m.start).expect("move outside of map");
+        let end = map.square(m.end).expect("move outside of map");
+        if self.region.contains(end) {
+            if !Self::is_locally_connected(map, end) {
+                self.region = map.reachable();
+                return;
+            }
+            self.region.remove(end);
+        }
+
+        // the old square of the box opens the region to squares behind it
+        self.region.insert(start);
+        self.stack.push(start);
+        let offsets = map.offsets();
+        while let Some(square) = self.stack.pop() {
+            for offset in offsets {
+                let next = square.wrapping_add_signed(offset);
+                if !self.region.contains(next) && map.is_free_square(next) {
+                    self.region.insert(next);
+                    self.stack.push(next);
+                }
+            }
+        }
+    }
+
+    /// Walking distance of the player to `square`, if it is reachable.
+    pub fn distance(&mut self, map: &Map, square: usize) -> Option<Cost> {
+        if !self.region.contains(square) {
+            return None;
+        }
+        if self.queue.is_empty() {
+            let start = map.square(map.player())?;
+            self.dist.resize(map.squares(), Cost::MAX);
+            self.dist[start] = 0;
+            self.queue.push(start);
+        }
+        let offsets = map.offsets();
+        while self.dist[square] == Cost::MAX && self.expanded < self.queue.len() {
+            let current = self.queue[self.expanded];
+            self.expanded += 1;
+            let cost = self.dist[current].saturating_add(1);
+            for offset in offsets {
+                let next = current.wrapping_add_signed(offset);
+                if self.dist[next] == Cost::MAX && self.region.contains(next) {
+                    self.dist[next] = cost;
+                    self.queue.push(next);
+                }
+            }
+        }
+        Some(self.dist[square]).filter(|cost| *cost != Cost::MAX)
+    }
+
+    fn invalidate_distances(&mut self) {
+        for square in self.queue.drain(..) {
+            self.dist[square] = Cost::MAX;
+        }
+        self.expanded = 0;
+    }
+
+    /// Checks whether all free neighbors of `square` are connected through the eight squares
+    /// around it, so that blocking `square` can not split the region.
+    fn is_locally_connected(map: &Map, square: usize) -> bool {
+        let [up, down, left, right] = map.offsets();
+        let ring = [
+            up + left,
+            up,
+            up + right,
+            right,
+            down + right,
+            down,
+            down + left,
+            left,
+        ];
+        let free = ring.map(|offset| map.is_free_square(square.wrapping_add_signed(offset)));
+
+        let blocked = match free.iter().position(|f| !f) {
+            Some(blocked) => blocked,
+            None => return true,
+        };
+
+        // label consecutive free squares of the ring, starting after a blocked one
+        let mut arc = 0;
+        let mut neighbor_arc = None;
+        for i in 1..=ring.len() {
+            let i = (blocked + i) % ring.len();
+            if !free[i] {
+                arc += 1;
+                continue;
+            }
+            // odd ring positions are the direct neighbors
+            if i % 2 == 1 {
+                match neighbor_arc {
+                    None => neighbor_arc = Some(arc),
+                    Some(a) if a != arc => return false,
+                    _ => {}
+                }
+            }
+        }
+        true
+    }
+}
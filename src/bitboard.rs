@@ -0,0 +1,186 @@
+use std::ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, Not};
+
+const WORD_BITS: usize = u64::BITS as usize;
+
+/// Set of squares of a board, one bit per square.
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+pub struct BitBoard {
+    len: usize,
+    words: Box<[u64]>,
+}
+
+impl BitBoard {
+    /// Empty set for a board with `len` squares.
+    pub fn new(len: usize) -> Self {
+        Self {
+            len,
+            words: vec![0; len.div_ceil(WORD_BITS)].into_boxed_slice(),
+        }
+    }
+
+    /// Set containing all `len` squares.
+    pub fn full(len: usize) -> Self {
+        let mut result = Self {
+            len,
+            words: vec![u64::MAX; len.div_ceil(WORD_BITS)].into_boxed_slice(),
+        };
+        result.clear_tail();
+        result
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    #[inline]
+    pub fn contains(&self, square: usize) -> bool {
+        square < self.len && self.words[square / WORD_BITS] & (1 << (square % WORD_BITS)) != 0
+    }
+
+    #[inline]
+    pub fn insert(&mut self, square: usize) {
+        assert!(square < self.len, "square {} out of bounds {}", square, self.len);
+        self.words[square / WORD_BITS] |= 1 << (square % WORD_BITS);
+    }
+
+    #[inline]
+    pub fn remove(&mut self, square: usize) {
+        assert!(square < self.len, "square {} out of bounds {}", square, self.len);
+        self.words[square / WORD_BITS] &= !(1 << (square % WORD_BITS));
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.iter().all(|w| *w == 0)
+    }
+
+    pub fn count(&self) -> usize {
+        self.words.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    /// Moves every square by `offset`. Squares moved out of the board are dropped.
+    pub fn shifted(&self, offset: isize) -> Self {
+        let mut result = Self::new(self.len);
+        let word_shift = offset.unsigned_abs() / WORD_BITS;
+        let bit_shift = offset.unsigned_abs() % WORD_BITS;
+        let n = self.words.len();
+        if offset >= 0 {
+            for i in (word_shift..n).rev() {
+                let src = i - word_shift;
+                let mut word = self.words[src] << bit_shift;
+                if bit_shift != 0 && src > 0 {
+                    word |= self.words[src - 1] >> (WORD_BITS - bit_shift);
+                }
+                result.words[i] = word;
+            }
+        } else {
+            for i in 0..n.saturating_sub(word_shift) {
+                let src = i + word_shift;
+                let mut word = self.words[src] >> bit_shift;
+                if bit_shift != 0 && src + 1 < n {
+                    word |= self.words[src + 1] << (WORD_BITS - bit_shift);
+                }
+                result.words[i] = word;
+            }
+        }
+        result.clear_tail();
+        result
+    }
+
+    /// Squares which are in `self` but not in `other`.
+    pub fn difference(&self, other: &Self) -> Self {
+        let mut result = self.clone();
+        for (a, b) in result.words.iter_mut().zip(other.words.iter()) {
+            *a &= !b;
+        }
+        result
+    }
+
+    /// All squares reachable from `self` by steps of `offsets` without leaving `free`.
+    pub fn flood_fill(&self, free: &Self, offsets: &[isize]) -> Self {
+        let mut reached = self.clone();
+        loop {
+            let mut next = reached.clone();
+            for offset in offsets {
+                next |= &reached.shifted(*offset);
+            }
+            next &= free;
+            next |= self;
+            if next == reached {
+                return reached;
+            }
+            reached = next;
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        self.words.iter().enumerate().flat_map(|(i, word)| {
+            let mut word = *word;
+            std::iter::from_fn(move || {
+                if word == 0 {
+                    return None;
+                }
+                let bit = word.trailing_zeros() as usize;
+                word &= word - 1;
+                Some(i * WORD_BITS + bit)
+            })
+        })
+    }
+
+    fn clear_tail(&mut self) {
+        let rest = self.len % WORD_BITS;
+        if rest != 0 {
+            if let Some(last) = self.words.last_mut() {
+                *last &= (1 << rest) - 1;
+            }
+        }
+    }
+}
+
+impl BitAndAssign<&BitBoard> for BitBoard {
+    fn bitand_assign(&mut self, rhs: &BitBoard) {
+        for (a, b) in self.words.iter_mut().zip(rhs.words.iter()) {
+            *a &= b;
+        }
+    }
+}
+
+impl BitOrAssign<&BitBoard> for BitBoard {
+    fn bitor_assign(&mut self, rhs: &BitBoard) {
+        for (a, b) in self.words.iter_mut().zip(rhs.words.iter()) {
+            *a |= b;
+        }
+    }
+}
+
+impl BitAnd<&BitBoard> for &BitBoard {
+    type Output = BitBoard;
+
+    fn bitand(self, rhs: &BitBoard) -> BitBoard {
+        let mut result = self.clone();
+        result &= rhs;
+        result
+    }
+}
+
+impl BitOr<&BitBoard> for &BitBoard {
+    type Output = BitBoard;
+
+    fn bitor(self, rhs: &BitBoard) -> BitBoard {
+        let mut result = self.clone();
+        result |= rhs;
+        result
+    }
+}
+
+impl Not for &BitBoard {
+    type Output = BitBoard;
+
+    fn not(self) -> BitBoard {
+        let mut result = self.clone();
+        for w in result.words.iter_mut() {
+            *w = !*w;
+        }
+        result.clear_tail();
+        result
+    }
+}
@@ -0,0 +1,222 @@
+//! Difficulty report of a level.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::time::{Duration, Instant};
+
+use crate::floor::{BoxSet, NO_FLOOR};
+use crate::solver::{Solver, SolverConfig, Strategy};
+use crate::statespace::StateSpace;
+use crate::{Costs, Input, Map, Push, Solution, SolveState};
+
+/// Measures of how hard a level is.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DifficultyReport {
+    /// Costs of an optimal solution.
+    pub costs: Costs,
+    /// States the solver expanded to find the optimal solution.
+    pub expanded: usize,
+    /// Average number of successors of an expanded state.
+    pub branching: f64,
+    /// Pushes of the optimal solution which do not continue a straight line of pushes of the
+    /// same box.
+    pub box_lines: usize,
+    /// Pushes of the optimal solution which push another box than the push before, counting
+    /// the first push.
+    pub box_changes: usize,
+    /// States of the optimal solution where one of the pushes the player can reach leads
+    /// into a deadlock.
+    pub deadlock_decisions: usize,
+    /// Distinct push sequences with optimal costs. `None` if the state space of the level is
+    /// too large.
+    pub optimal_solutions: Option<u64>,
+    /// Fraction of the states reachable from the start from which the level can not be
+    /// solved anymore. `None` if the state space of the level is too large.
+    pub dead_end_ratio: Option<f64>,
+}
+
+impl DifficultyReport {
+    /// Measures the level of `input`. The solver gives up after `timeout`, the exact measures
+    /// are only computed for levels with at most `max_states` states. Returns `None` if no
+    /// solution is found.
+    pub fn measure(input: &Input, timeout: Duration, max_states: usize) -> Option<Self> {
+        let map = Map::from(input.clone());
+        let config = SolverConfig {
+            strategy: Strategy::UniformCost,
+            freeze_deadlocks: false,
+            deadline: Some(Instant::now() + timeout),
+            ..SolverConfig::default()
+        };
+        let (solution, stats) = Solver::with_config(config).solve_with_stats(&map);
+        let solution = solution?;
+
+        let space = StateSpace::enumerate(&map, max_states);
+        Some(Self {
+            costs: solution.costs(),
+            expanded: stats.expanded,
+            branching: stats.generated as f64 / stats.expanded.max(1) as f64,
+            box_lines: solution.box_lines(),
+            box_changes: solution.box_changes(),
+            deadlock_decisions: Self::deadlock_decisions(&map, &solution),
+            optimal_solutions: space
+                .as_ref()
+                .map(|space| Self::optimal_solutions(&map, space)),
+            dead_end_ratio: space
+                .as_ref()
+                .and_then(|space| Self::dead_end_ratio(&map, space, max_states)),
+        })
+    }
+
+    fn deadlock_decisions(map: &Map, solution: &Solution) -> usize {
+        let mut map = map.clone();
+        let mut decisions = 0;
+        for m in solution.moves() {
+            let reachable = map.reachable();
+            let leads_to_deadlock = Self::pushes(&map, |square| reachable.contains(square))
+                .into_iter()
+                .any(|push| {
+                    let mut next = map.clone();
+                    next.apply_push(push);
+                    let to = push.to as usize;
+                    !next.is_destination_square(to)
+                        && (next.is_dead_square(to) || next.is_freeze_deadlock_square(to))
+                });
+            if leads_to_deadlock {
+                decisions += 1;
+            }
+            map.apply_move(*m);
+        }
+        decisions
+    }
+
+    /// All pushes with the player on a square accepted by `player`, including pushes to dead
+    /// squares.
+    fn pushes(map: &Map, player: impl Fn(usize) -> bool) -> Vec<Push> {
+        let mut pushes = vec![];
+        for b in map.boxes() {
+            let from = map.square(b).unwrap();
+            for offset in map.offsets() {
+                let to = from.wrapping_add_signed(offset);
+                if map.is_free_square(to) && player(from.wrapping_add_signed(-offset)) {
+                    pushes.push(Push {
+                        from: from as u32,
+                        to: to as u32,
+                    });
+                }
+            }
+        }
+        pushes
+    }
+
+    /// Counts the push sequences which follow optimal solutions through the state space.
+    fn optimal_solutions(map: &Map, space: &StateSpace) -> u64 {
+        let mut counts = HashMap::new();
+        Self::count_optimal(map, space, map.solve_state(), &mut counts)
+    }
+
+    fn count_optimal(
+        map: &Map,
+        space: &StateSpace,
+        state: &SolveState,
+        counts: &mut HashMap<SolveState, u64>,
+    ) -> u64 {
+        let remaining = match space.remaining(state) {
+            Some(remaining) => remaining,
+            None => return 0,
+        };
+        if remaining.pushes == 0 {
+            return 1;
+        }
+        if let Some(count) = counts.get(state) {
+            return *count;
+        }
+
+        let floor = map.floor();
+        let distances = Self::walk_distances(map, state);
+        let mut count = 0u64;
+        for b in state.boxes.iter() {
+            let neighbors = floor.neighbors(b);
+            for (direction, &to) in neighbors.iter().enumerate() {
+                let player = neighbors[direction ^ 1];
+                if to == NO_FLOOR || player == NO_FLOOR || state.boxes.contains(to) {
+                    continue;
+                }
+                let walk = match distances.get(&player) {
+                    Some(walk) => *walk,
+                    None => continue,
+                };
+                let mut next = state.clone();
+                next.apply_push(floor, b, to);
+                let optimal = space.remaining(&next).is_some_and(|rest| {
+                    Costs::new(rest.pushes + 1, rest.moves + walk + 1) == remaining
+                });
+                if optimal {
+                    count = count.saturating_add(Self::count_optimal(map, space, &next, counts));
+                }
+            }
+        }
+        counts.insert(state.clone(), count);
+        count
+    }
+
+    /// Walking distances of the player over floor indices.
+    fn walk_distances(map: &Map, state: &SolveState) -> HashMap<u32, u32> {
+        let floor = map.floor();
+        let mut distances = HashMap::from([(state.player, 0)]);
+        let mut queue = VecDeque::from([state.player]);
+        while let Some(square) = queue.pop_front() {
+            let distance = distances[&square];
+            for next in floor.neighbors(square) {
+                if next != NO_FLOOR && !state.boxes.contains(next) && !distances.contains_key(&next)
+                {
+                    distances.insert(next, distance + 1);
+                    queue.push_back(next);
+                }
+            }
+        }
+        distances
+    }
+
+    /// Explores the states reachable from the start by pushes. States are identified by the
+    /// boxes and the region of the player, so that walking does not create new states.
+    fn dead_end_ratio(map: &Map, space: &StateSpace, max_states: usize) -> Option<f64> {
+        let floor = map.floor();
+        let normalize = |player: u32, boxes: &BoxSet| {
+            let state = SolveState::new(player, boxes.clone(), floor);
+            let player = *Self::walk_distances(map, &state).keys().min().unwrap();
+            SolveState::new(player, boxes.clone(), floor)
+        };
+
+        let start = normalize(map.solve_state().player, &map.solve_state().boxes);
+        let mut seen = HashSet::from([start.clone()]);
+        let mut queue = vec![start];
+        let mut dead_ends = 0;
+        while let Some(state) = queue.pop() {
+            // walking does not change solvability, so any square of the region will do
+            if space.remaining(&state).is_none() {
+                dead_ends += 1;
+                continue;
+            }
+            let region = Self::walk_distances(map, &state);
+            for b in state.boxes.iter() {
+                let neighbors = floor.neighbors(b);
+                for (direction, &to) in neighbors.iter().enumerate() {
+                    let player = neighbors[direction ^ 1];
+                    if to == NO_FLOOR || state.boxes.contains(to) || !region.contains_key(&player) {
+                        continue;
+                    }
+                    let mut boxes = state.boxes.clone();
+                    boxes.remove(b);
+                    boxes.insert(to);
+                    let next = normalize(b, &boxes);
+                    if seen.insert(next.clone()) {
+                        queue.push(next);
+                    }
+                }
+            }
+            if seen.len() > max_states {
+                return None;
+            }
+        }
+        Some(dead_ends as f64 / seen.len() as f64)
+    }
+}
@@ -0,0 +1,162 @@
+use crate::bitboard::BitBoard;
+
+/// Marks a missing floor square in `Floor::index` and `Floor::neighbors`.
+pub const NO_FLOOR: u32 = u32::MAX;
+
+const WORD_BITS: usize = u64::BITS as usize;
+
+/// Compact numbering of the floor squares of a level. Only squares the player can walk to
+/// from the start position (ignoring boxes) and squares with boxes or destinations are
+/// numbered, walls and decorative floor outside of the walls are not.
+pub struct Floor {
+    /// Square of the padded board for each floor index.
+    squares: Box<[u32]>,
+    /// Floor index for each square of the padded board.
+    index: Box<[u32]>,
+    /// Floor indices of the neighbors in the order up, down, left, right.
+    neighbors: Box<[[u32; 4]]>,
+    /// Random keys for Zobrist hashing of box sets.
+    zobrist: Box<[u64]>,
+}
+
+impl Floor {
+    /// Numbers all squares reachable from `start` without crossing `walls` plus `extra`.
+    pub fn new(walls: &BitBoard, offsets: [isize; 4], start: usize, extra: &[usize]) -> Self {
+        let mut seed = BitBoard::new(walls.len());
+        seed.insert(start);
+        for square in extra {
+            seed.insert(*square);
+        }
+        let floor = seed.flood_fill(&!walls, &offsets);
+
+        let squares: Box<[u32]> = floor.iter().map(|square| square as u32).collect();
+        let mut index = vec![NO_FLOOR; walls.len()].into_boxed_slice();
+        for (i, square) in squares.iter().enumerate() {
+            index[*square as usize] = i as u32;
+        }
+        let neighbors = squares
+            .iter()
+            .map(|square| {
+                offsets.map(|offset| {
+                    index
+                        .get((*square as usize).wrapping_add_signed(offset))
+                        .copied()
+                        .unwrap_or(NO_FLOOR)
+                })
+            })
+            .collect();
+
+        let mut rng = 0x2545_f491_4f6c_dd1d;
+        let zobrist = squares.iter().map(|_| split_mix64(&mut rng)).collect();
+
+        Self {
+            squares,
+            index,
+            neighbors,
+            zobrist,
+        }
+    }
+
+    /// Number of floor squares.
+    pub fn len(&self) -> usize {
+        self.squares.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.squares.is_empty()
+    }
+
+    /// Floor index of a square of the padded board.
+    #[inline]
+    pub fn index(&self, square: usize) -> Option<u32> {
+        match self.index.get(square) {
+            Some(&i) if i != NO_FLOOR => Some(i),
+            _ => None,
+        }
+    }
+
+    /// Square of the padded board of a floor index.
+    #[inline]
+    pub fn square(&self, floor: u32) -> usize {
+        self.squares[floor as usize] as usize
+    }
+
+    /// Floor indices of the neighbors in the order up, down, left, right. Missing neighbors
+    /// are `NO_FLOOR`.
+    #[inline]
+    pub fn neighbors(&self, floor: u32) -> [u32; 4] {
+        self.neighbors[floor as usize]
+    }
+
+    #[inline]
+    pub fn zobrist(&self, floor: u32) -> u64 {
+        self.zobrist[floor as usize]
+    }
+}
+
+pub(crate) fn split_mix64(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+/// Set of floor indices, one bit per floor square.
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub struct BoxSet(Box<[u64]>);
+
+impl BoxSet {
+    /// Empty set for a level with `len` floor squares.
+    pub fn new(len: usize) -> Self {
+        Self(vec![0; len.div_ceil(WORD_BITS)].into_boxed_slice())
+    }
+
+    #[inline]
+    pub fn contains(&self, floor: u32) -> bool {
+        let floor = floor as usize;
+        self.0
+            .get(floor / WORD_BITS)
+            .is_some_and(|w| w & (1 << (floor % WORD_BITS)) != 0)
+    }
+
+    #[inline]
+    pub fn insert(&mut self, floor: u32) {
+        let floor = floor as usize;
+        self.0[floor / WORD_BITS] |= 1 << (floor % WORD_BITS);
+    }
+
+    #[inline]
+    pub fn remove(&mut self, floor: u32) {
+        let floor = floor as usize;
+        self.0[floor / WORD_BITS] &= !(1 << (floor % WORD_BITS));
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.iter().all(|w| *w == 0)
+    }
+
+    /// Floor indices in ascending order.
+    pub fn iter(&self) -> impl Iterator<Item = u32> + '_ {
+        self.0.iter().enumerate().flat_map(|(i, word)| {
+            let mut word = *word;
+            std::iter::from_fn(move || {
+                if word == 0 {
+                    return None;
+                }
+                let bit = word.trailing_zeros() as usize;
+                word &= word - 1;
+                Some((i * WORD_BITS + bit) as u32)
+            })
+        })
+    }
+
+    /// Bytes used on the heap.
+    pub fn heap_size(&self) -> usize {
+        std::mem::size_of_val(&*self.0)
+    }
+}
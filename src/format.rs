@@ -0,0 +1,147 @@
+//! Standard text formats of levels: XSB, the plain format of the level files, and its
+//! run-length encoded variant used by online Sokoban sites.
+
+use std::fmt;
+use std::fmt::Write;
+
+use crate::algos::bfs::DistanceMap;
+use crate::{CellState, Direction, Input, InputError, Map, Solution, SolveState, StateError, MAX_SIZE};
+
+/// Separator of rows in the run-length encoded format.
+const RLE_ROW: char = '|';
+
+impl fmt::Display for Input {
+    /// Writes the level in XSB format without trailing spaces.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (y, line) in self.input.iter().enumerate() {
+            if y > 0 {
+                f.write_char('\n')?;
+            }
+            let end = line
+                .iter()
+                .rposition(|cell| *cell != CellState::Empty)
+                .map_or(0, |x| x + 1);
+            for cell in &line[..end] {
+                f.write_char(cell.to_char())?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Input {
+    /// Run-length encoded level, e.g. `4#|#@$.#|4#`. Empty squares are written as `-`.
+    pub fn to_rle(&self) -> String {
+        let mut result = String::new();
+        for (y, line) in self.input.iter().enumerate() {
+            if y > 0 {
+                result.push(RLE_ROW);
+            }
+            let end = line
+                .iter()
+                .rposition(|cell| *cell != CellState::Empty)
+                .map_or(0, |x| x + 1);
+            let mut cells = line[..end].iter().peekable();
+            while let Some(cell) = cells.next() {
+                let mut count = 1;
+                while cells.next_if_eq(&cell).is_some() {
+                    count += 1;
+                }
+                if count > 1 {
+                    write!(result, "{}", count).unwrap();
+                }
+                result.push(match cell {
+                    CellState::Empty => '-',
+                    other => other.to_char(),
+                });
+            }
+        }
+        result
+    }
+
+    /// Parses a run-length encoded level. Rows are separated by `|` or line breaks.
+    pub fn from_rle(s: &str) -> Result<Input, InputError> {
+        let mut input = vec![];
+        let mut line = vec![];
+        let mut count: Option<usize> = None;
+        for c in s.trim().chars() {
+            if let Some(digit) = c.to_digit(10) {
+                count = Some(
+                    count
+                        .unwrap_or_default()
+                        .saturating_mul(10)
+                        .saturating_add(digit as usize),
+                );
+                continue;
+            }
+            if c == RLE_ROW || c == '\n' {
+                if count.is_some() {
+                    return Err(InputError::MissingCell);
+                }
+                input.push(std::mem::take(&mut line));
+                continue;
+            }
+            if c == '\r' {
+                continue;
+            }
+            let cell = CellState::from_char(c)?;
+            // check the size before the run is expanded
+            let width = line.len().saturating_add(count.take().unwrap_or(1));
+            if width > MAX_SIZE {
+                return Err(InputError::TooLarge {
+                    width,
+                    height: input.len() + 1,
+                });
+            }
+            line.resize(width, cell);
+        }
+        if count.is_some() {
+            return Err(InputError::MissingCell);
+        }
+        input.push(line);
+        Input::from_rows(input)
+    }
+}
+
+impl Map {
+    /// Level in XSB format in the current state.
+    pub fn to_xsb(&self) -> String {
+        self.to_string()
+    }
+
+    /// Level in XSB format in `state`. Fails if `state` is not a state of this level.
+    pub fn to_xsb_with_state(&self, state: &SolveState) -> Result<String, StateError> {
+        Ok(self.with_checked_solve_state(state)?.to_xsb())
+    }
+}
+
+impl Solution {
+    /// Player path in LURD notation, with the shortest walks between the pushes. Returns
+    /// `None` if the solution does not belong to `map`.
+    pub fn to_lurd(&self, map: &Map) -> Option<String> {
+        let mut map = map.clone();
+        let mut distances = DistanceMap::new();
+        let mut result = String::new();
+        for m in &self.moves {
+            distances.update(&map);
+            let mut walk = vec![];
+            let mut square = map.square(m.player_pos()?)?;
+            let mut distance = distances.distance(square)?;
+            // walk back to the player along decreasing distances
+            while distance > 0 {
+                let (direction, previous) = Direction::ALL
+                    .iter()
+                    .zip(map.offsets())
+                    .map(|(d, offset)| (d.reverse(), square.wrapping_add_signed(offset)))
+                    .find(|(_, previous)| distances.distance(*previous) == Some(distance - 1))?;
+                walk.push(direction.to_lurd(false));
+                square = previous;
+                distance -= 1;
+            }
+            result.extend(walk.iter().rev());
+            result.push(m.direction.to_lurd(true));
+            map.apply_move(*m);
+        }
+        Some(result)
+    }
+}
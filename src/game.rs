@@ -0,0 +1,137 @@
+//! State of a level played by a human, with undo and redo.
+
+use std::time::{Duration, Instant};
+
+use crate::solver::{Solver, SolverConfig};
+use crate::{Costs, Direction, Map, Move, Pos, SolveState};
+
+/// Result of a step of the player.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum Step {
+    Walk,
+    Push,
+    /// The player can not move in this direction, nothing changed.
+    Blocked,
+}
+
+/// A level played step by step.
+pub struct Game {
+    map: Map,
+    costs: Costs,
+    /// States before each step and the step in LURD notation.
+    undo: Vec<(SolveState, Costs, char)>,
+    /// Undone steps in LURD notation, the next one last.
+    redo: Vec<char>,
+}
+
+impl Game {
+    pub fn new(map: Map) -> Self {
+        Self {
+            map,
+            costs: Costs::zero(),
+            undo: vec![],
+            redo: vec![],
+        }
+    }
+
+    pub fn map(&self) -> &Map {
+        &self.map
+    }
+
+    pub fn costs(&self) -> Costs {
+        self.costs
+    }
+
+    pub fn is_solved(&self) -> bool {
+        self.map.is_solved()
+    }
+
+    /// Steps played so far in LURD notation.
+    pub fn lurd(&self) -> String {
+        self.undo.iter().map(|(_, _, c)| *c).collect()
+    }
+
+    /// Walks in `direction` or pushes the box in front of the player.
+    pub fn step(&mut self, direction: Direction) -> Step {
+        let result = self.apply(direction);
+        if result != Step::Blocked {
+            self.redo.clear();
+        }
+        result
+    }
+
+    pub fn undo(&mut self) -> bool {
+        match self.undo.pop() {
+            Some((state, costs, c)) => {
+                self.map = self.map.with_solve_state(state);
+                self.costs = costs;
+                self.redo.push(c);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn redo(&mut self) -> bool {
+        match self.redo.pop().and_then(Direction::from_lurd) {
+            Some((direction, _)) => self.apply(direction) != Step::Blocked,
+            None => false,
+        }
+    }
+
+    /// Goes back to the start of the level. The steps can be redone.
+    pub fn restart(&mut self) {
+        while self.undo() {}
+    }
+
+    /// Boxes which can not reach a destination anymore: boxes on dead squares and frozen
+    /// boxes.
+    pub fn deadlocks(&self) -> Vec<Pos> {
+        self.map
+            .boxes()
+            .filter(|pos| {
+                !self.map.is_destination(*pos)
+                    && (self.map.is_dead(*pos) || self.map.is_freeze_deadlock(*pos))
+            })
+            .collect()
+    }
+
+    /// Next push of a solution with the least pushes from the current state, if one is found
+    /// within `timeout`.
+    pub fn hint(&self, timeout: Duration) -> Option<Move> {
+        let config = SolverConfig {
+            freeze_deadlocks: true,
+            deadline: Some(Instant::now() + timeout),
+            ..SolverConfig::default()
+        };
+        let (solution, _) = Solver::with_config(config).solve_with_stats(&self.map);
+        solution.and_then(|solution| solution.moves().first().copied())
+    }
+
+    fn apply(&mut self, direction: Direction) -> Step {
+        let next = match self.map.player().step(direction) {
+            Some(next) => next,
+            None => return Step::Blocked,
+        };
+
+        let state = self.map.solve_state().clone();
+        let costs = self.costs;
+        let result = if self.map.is_free(next) {
+            self.map.set_player_pos(next);
+            Step::Walk
+        } else {
+            match Move::in_direction(next, direction) {
+                Some(m) if self.map.is_box(next) && self.map.is_free(m.end) => {
+                    self.map.apply_move(m);
+                    self.costs.pushes += 1;
+                    Step::Push
+                }
+                _ => return Step::Blocked,
+            }
+        };
+        self.costs.moves += 1;
+        self.undo
+            .push((state, costs, direction.to_lurd(result == Step::Push)));
+        result
+    }
+}
@@ -0,0 +1,270 @@
+//! Random levels which are solvable by construction.
+//!
+//! A level is generated in three steps: a random room is carved out of a block of walls,
+//! goals are placed on its floor and boxes are pulled away from the goals by a random walk of
+//! the player. Pulls are pushes in reverse, so the level can always be solved. The solver
+//! verifies and scores each candidate.
+
+use std::str::FromStr;
+
+use crate::floor::split_mix64;
+use crate::solver::{Solver, SolverConfig, Strategy};
+use crate::{Input, Map, Solution};
+
+#[derive(Clone, Debug)]
+pub struct GeneratorConfig {
+    /// Width of the level including the outer walls.
+    pub width: usize,
+    /// Height of the level including the outer walls.
+    pub height: usize,
+    pub boxes: usize,
+    /// Fraction of the inner squares which become walls.
+    pub wall_density: f64,
+    /// Pulls of the random walk which creates the start position.
+    pub pulls: usize,
+    /// Minimal pushes of an optimal solution.
+    pub target_pushes: u32,
+    /// Candidates to try before giving up.
+    pub attempts: usize,
+    /// States the solver may expand for each candidate. A budget instead of a timeout keeps
+    /// the generated levels independent of the speed of the machine.
+    pub solver_budget: usize,
+    pub seed: u64,
+}
+
+impl Default for GeneratorConfig {
+    fn default() -> Self {
+        Self {
+            width: 9,
+            height: 8,
+            boxes: 3,
+            wall_density: 0.2,
+            pulls: 40,
+            target_pushes: 10,
+            attempts: 100,
+            solver_budget: 1_000_000,
+            seed: 0,
+        }
+    }
+}
+
+/// Difficulty of a generated level, measured by solving it.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Score {
+    pub pushes: u32,
+    pub moves: u32,
+    /// States the solver expanded.
+    pub expanded: usize,
+    /// Average number of successors of an expanded state.
+    pub branching: f64,
+}
+
+pub struct GeneratedLevel {
+    pub input: Input,
+    pub solution: Solution,
+    pub score: Score,
+}
+
+impl GeneratedLevel {
+    /// Level in XSB format.
+    pub fn xsb(&self) -> String {
+        self.input.to_string()
+    }
+}
+
+/// Generates levels deterministically from the seed of its config.
+pub struct Generator {
+    config: GeneratorConfig,
+    rng: u64,
+}
+
+impl Generator {
+    pub fn new(config: GeneratorConfig) -> Self {
+        let rng = config.seed;
+        Self { config, rng }
+    }
+
+    /// Generates candidates until one reaches the target pushes. Returns `None` if no
+    /// candidate did within the configured attempts.
+    pub fn generate(&mut self) -> Option<GeneratedLevel> {
+        (0..self.config.attempts).find_map(|_| {
+            let level = self.candidate()?;
+            (level.score.pushes >= self.config.target_pushes).then_some(level)
+        })
+    }
+
+    /// One random level, verified by the solver.
+    pub fn candidate(&mut self) -> Option<GeneratedLevel> {
+        let (width, height) = (self.config.width, self.config.height);
+        if width < 3 || height < 3 {
+            return None;
+        }
+        let mut cells = self.room();
+        let floor: Vec<usize> = (0..cells.len()).filter(|i| cells[*i] != '#').collect();
+        if floor.len() <= self.config.boxes {
+            return None;
+        }
+
+        // goals with the boxes on them, the player somewhere else
+        let mut goals = vec![];
+        while goals.len() < self.config.boxes {
+            let square = floor[self.below(floor.len())];
+            if !goals.contains(&square) {
+                goals.push(square);
+            }
+        }
+        let mut boxes = goals.clone();
+        let free: Vec<usize> = floor
+            .iter()
+            .copied()
+            .filter(|s| !goals.contains(s))
+            .collect();
+        let mut player = free[self.below(free.len())];
+
+        for _ in 0..self.config.pulls {
+            let pulls = Self::pulls(&cells, width, &boxes, player);
+            if pulls.is_empty() {
+                break;
+            }
+            let (b, to, behind) = pulls[self.below(pulls.len())];
+            let i = boxes.iter().position(|x| *x == b).unwrap();
+            boxes[i] = to;
+            player = behind;
+        }
+        // let the player end anywhere it can walk to
+        let region = Self::region(&cells, width, &boxes, player);
+        player = region[self.below(region.len())];
+
+        for square in &goals {
+            cells[*square] = '.';
+        }
+        for square in &boxes {
+            cells[*square] = if cells[*square] == '.' { '*' } else { '$' };
+        }
+        cells[player] = if cells[player] == '.' { '+' } else { '@' };
+        let text: Vec<String> = cells
+            .chunks(width)
+            .map(|row| row.iter().collect())
+            .collect();
+        let (input, _) = Input::from_str(&text.join("\n")).ok()?.normalized();
+
+        let map = Map::from(input.clone());
+        if map.is_solved() {
+            return None;
+        }
+        let config = SolverConfig {
+            strategy: Strategy::UniformCost,
+            freeze_deadlocks: true,
+            max_expanded: Some(self.config.solver_budget),
+            ..SolverConfig::default()
+        };
+        let (solution, stats) = Solver::with_config(config).solve_with_stats(&map);
+        let solution = solution?;
+        let score = Score {
+            pushes: solution.costs().pushes,
+            moves: solution.costs().moves,
+            expanded: stats.expanded,
+            branching: stats.generated as f64 / stats.expanded.max(1) as f64,
+        };
+        Some(GeneratedLevel {
+            input,
+            solution,
+            score,
+        })
+    }
+
+    /// Random room: a block of walls with inner floor, of which random squares become walls.
+    /// Only the largest connected part of the floor is kept.
+    fn room(&mut self) -> Vec<char> {
+        let (width, height) = (self.config.width, self.config.height);
+        let mut cells = vec!['#'; width * height];
+        for y in 1..height - 1 {
+            for x in 1..width - 1 {
+                if self.chance() >= self.config.wall_density {
+                    cells[y * width + x] = ' ';
+                }
+            }
+        }
+
+        let mut largest: Vec<usize> = vec![];
+        let mut seen = vec![false; cells.len()];
+        for start in 0..cells.len() {
+            if cells[start] == '#' || seen[start] {
+                continue;
+            }
+            seen[start] = true;
+            let mut part = vec![start];
+            let mut i = 0;
+            while i < part.len() {
+                for next in Self::neighbors(part[i], width) {
+                    if cells[next] != '#' && !seen[next] {
+                        seen[next] = true;
+                        part.push(next);
+                    }
+                }
+                i += 1;
+            }
+            if part.len() > largest.len() {
+                largest = part;
+            }
+        }
+
+        let mut room = vec!['#'; cells.len()];
+        for square in largest {
+            room[square] = ' ';
+        }
+        room
+    }
+
+    /// Squares the player can walk to.
+    fn region(cells: &[char], width: usize, boxes: &[usize], player: usize) -> Vec<usize> {
+        let mut region = vec![player];
+        let mut i = 0;
+        while i < region.len() {
+            for next in Self::neighbors(region[i], width) {
+                if cells[next] != '#' && !boxes.contains(&next) && !region.contains(&next) {
+                    region.push(next);
+                }
+            }
+            i += 1;
+        }
+        region
+    }
+
+    /// Possible pulls as box square, new box square and new player square.
+    fn pulls(
+        cells: &[char],
+        width: usize,
+        boxes: &[usize],
+        player: usize,
+    ) -> Vec<(usize, usize, usize)> {
+        let free = |square: usize| cells[square] != '#' && !boxes.contains(&square);
+        let mut pulls = vec![];
+        for square in Self::region(cells, width, boxes, player) {
+            for b in Self::neighbors(square, width) {
+                if !boxes.contains(&b) {
+                    continue;
+                }
+                // the player steps back from the box
+                let behind = (2 * square).wrapping_sub(b);
+                if behind < cells.len() && free(behind) {
+                    pulls.push((b, square, behind));
+                }
+            }
+        }
+        pulls
+    }
+
+    /// Neighbors of an inner square.
+    fn neighbors(square: usize, width: usize) -> [usize; 4] {
+        [square - width, square + width, square - 1, square + 1]
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        (split_mix64(&mut self.rng) % n as u64) as usize
+    }
+
+    fn chance(&mut self) -> f64 {
+        (split_mix64(&mut self.rng) >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
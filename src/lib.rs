@@ -1,14 +1,34 @@
 use crate::algos::matrix::Matrix;
+use crate::bitboard::BitBoard;
+use crate::floor::{BoxSet, Floor, NO_FLOOR};
+use crate::matching::Matching;
+use crate::pushdistance::PushDistances;
+use crate::transform::Transform;
+use std::borrow::Cow;
 use std::fmt;
-use std::fmt::{Formatter, Write};
-use std::hash::Hash;
-use std::rc::Rc;
+use std::fmt::Formatter;
+use std::hash::{Hash, Hasher};
+use std::sync::{Arc, OnceLock};
 
 pub mod algos;
-
+pub mod bitboard;
+pub mod difficulty;
+pub mod floor;
+pub mod format;
+pub mod game;
+pub mod generator;
+mod matching;
+pub mod optimizer;
+pub mod oracle;
+pub mod portfolio;
+pub mod pushdistance;
+pub mod render;
+mod reverse;
 pub mod solver;
+pub mod statespace;
+pub mod transform;
 
-#[derive(Copy, Clone, Eq, PartialEq)]
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
 enum CellState {
     Empty,
     Wall,
@@ -52,12 +72,10 @@ impl CellState {
     }
 
     pub fn is_destination(self) -> bool {
-        match self {
-            CellState::Destination => true,
-            CellState::BoxOnDestination => true,
-            CellState::PlayerOnDestination => true,
-            _ => false,
-        }
+        matches!(
+            self,
+            CellState::Destination | CellState::BoxOnDestination | CellState::PlayerOnDestination
+        )
     }
 }
 
@@ -65,8 +83,28 @@ impl CellState {
 pub enum InputError {
     UnknownChar(char),
     MissingPlayer,
+    /// A repeat count of a run-length encoded level is not followed by a square.
+    MissingCell,
+    /// Levels are limited to `u16::MAX + 1` rows and columns.
+    TooLarge { width: usize, height: usize },
 }
 
+/// Reasons why positions are no legal state of a level.
+#[derive(Debug, Eq, PartialEq)]
+pub enum StateError {
+    /// The player is on a wall or outside of the area enclosed by the walls.
+    PlayerOffFloor(Pos),
+    /// A box is on a wall or outside of the area enclosed by the walls.
+    BoxOffFloor(Pos),
+    PlayerOnBox(Pos),
+    DuplicateBox(Pos),
+    /// The number of boxes differs from the number of destinations.
+    BoxCount { boxes: usize, destinations: usize },
+    /// The state belongs to a different level.
+    ForeignState,
+}
+
+#[derive(Clone, Eq, PartialEq, Debug)]
 pub struct Input {
     input: Vec<Vec<CellState>>,
 }
@@ -77,8 +115,8 @@ impl Input {
             for (x, cell) in line.iter().enumerate() {
                 if *cell == CellState::Player || *cell == CellState::PlayerOnDestination {
                     return Ok(Pos {
-                        x: x as u8,
-                        y: y as u8,
+                        x: x as u16,
+                        y: y as u16,
                     });
                 }
             }
@@ -93,8 +131,8 @@ impl Input {
             for (x, cell) in line.iter().enumerate() {
                 if *cell == CellState::Box || *cell == CellState::BoxOnDestination {
                     boxes.push(Pos {
-                        x: x as u8,
-                        y: y as u8,
+                        x: x as u16,
+                        y: y as u16,
                     });
                 }
             }
@@ -111,95 +149,433 @@ impl Input {
                     || *cell == CellState::PlayerOnDestination
                 {
                     boxes.push(Pos {
-                        x: x as u8,
-                        y: y as u8,
+                        x: x as u16,
+                        y: y as u16,
                     });
                 }
             }
         }
         boxes
     }
+
+    pub fn width(&self) -> usize {
+        self.input.iter().map(|line| line.len()).max().unwrap_or_default()
+    }
+
+    pub fn height(&self) -> usize {
+        self.input.len()
+    }
+
+    /// All lines extended with empty squares to the same length.
+    pub fn padded(&self) -> Input {
+        let width = self.width();
+        let mut input = self.input.clone();
+        for line in input.iter_mut() {
+            line.resize(width, CellState::Empty);
+        }
+        Input { input }
+    }
 }
 
 impl std::str::FromStr for Input {
     type Err = InputError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let input: Result<Vec<Vec<CellState>>, InputError> = s
+        let input: Vec<Vec<CellState>> = s
             .split('\n')
-            .map(|line| line.chars().map(|c| CellState::from_char(c)).collect())
-            .collect();
-        Ok(Self {
-            input: input.unwrap(),
-        })
+            .map(|line| line.chars().map(CellState::from_char).collect())
+            .collect::<Result<_, _>>()?;
+        Self::from_rows(input)
     }
 }
 
+impl Input {
+    fn from_rows(input: Vec<Vec<CellState>>) -> Result<Self, InputError> {
+        let width = input.iter().map(|line| line.len()).max().unwrap_or_default();
+        let height = input.len();
+        if width > MAX_SIZE || height > MAX_SIZE {
+            return Err(InputError::TooLarge { width, height });
+        }
+
+        Ok(Self { input })
+    }
+}
+
+/// Maximal number of rows and columns of a level.
+const MAX_SIZE: usize = u16::MAX as usize + 1;
+
+/// Static properties of a level. Squares are indexed over the board padded with one wall
+/// on each side, so that neighbors of board squares never leave the board.
 struct MapProps {
     width: usize,
     height: usize,
-    map: Matrix<bool>,
-    dead: Matrix<bool>,
-    destinations: Vec<Pos>,
+    stride: usize,
+    /// Index offsets of the neighbors of a square in the order up, down, left, right.
+    offsets: [isize; 4],
+    walls: BitBoard,
+    dead: BitBoard,
+    floor: Floor,
+    destinations: BoxSet,
+    destination_bits: BitBoard,
+    /// Manhattan distance of each floor square to the nearest destination.
+    destination_distance: Box<[u32]>,
+    /// Built on first use, most searches do not need it.
+    push_distances: OnceLock<PushDistances>,
+    /// Transforms other than the identity which map the level onto itself, with the
+    /// permutation of the floor indices they induce.
+    symmetries: Vec<(Transform, Box<[u32]>)>,
+}
+
+impl MapProps {
+    fn new(
+        width: usize,
+        height: usize,
+        walls: BitBoard,
+        dead: BitBoard,
+        player: Pos,
+        boxes: &[Pos],
+        destinations: &[Pos],
+    ) -> Self {
+        let stride = width + 2;
+        let offsets = [-(stride as isize), stride as isize, -1, 1];
+        let square = |pos: Pos| pos.x as usize + 1 + (pos.y as usize + 1) * stride;
+
+        let extra: Vec<usize> = boxes.iter().chain(destinations).map(|p| square(*p)).collect();
+        let floor = Floor::new(&walls, offsets, square(player), &extra);
+
+        let mut destination_bits = BitBoard::new(walls.len());
+        let mut destination_set = BoxSet::new(floor.len());
+        for d in destinations.iter() {
+            destination_bits.insert(square(*d));
+            destination_set.insert(floor.index(square(*d)).unwrap());
+        }
+
+        let coords = |square: usize| (square % stride, square / stride);
+        let destination_distance = (0..floor.len() as u32)
+            .map(|f| {
+                let (x, y) = coords(floor.square(f));
+                destination_set
+                    .iter()
+                    .map(|d| {
+                        let (dx, dy) = coords(floor.square(d));
+                        (x.abs_diff(dx) + y.abs_diff(dy)) as u32
+                    })
+                    .min()
+                    .unwrap_or_default()
+            })
+            .collect();
+
+        let mut props = Self {
+            width,
+            height,
+            stride,
+            offsets,
+            walls,
+            dead,
+            floor,
+            destinations: destination_set,
+            destination_bits,
+            destination_distance,
+            push_distances: OnceLock::new(),
+            symmetries: vec![],
+        };
+        props.symmetries = props.detect_symmetries();
+        props
+    }
+
+    /// Finds all transforms which map floor, dead squares and destinations onto themselves.
+    fn detect_symmetries(&self) -> Vec<(Transform, Box<[u32]>)> {
+        Transform::ALL[1..]
+            .iter()
+            .filter(|t| t.size(self.width, self.height) == (self.width, self.height))
+            .filter_map(|t| {
+                let permutation = (0..self.floor.len() as u32)
+                    .map(|f| {
+                        let square = self.floor.square(f);
+                        let pos = t.apply(self.pos(square), self.width, self.height);
+                        let image = self.square(pos)?;
+                        let same = self.dead.contains(square) == self.dead.contains(image)
+                            && self.destination_bits.contains(square)
+                                == self.destination_bits.contains(image);
+                        same.then(|| self.floor.index(image)).flatten()
+                    })
+                    .collect::<Option<Box<[u32]>>>()?;
+                Some((*t, permutation))
+            })
+            .collect()
+    }
+
+    /// Floor index of `square`, which has to be a floor square.
+    #[inline]
+    fn floor_index(&self, square: usize) -> u32 {
+        self.floor.index(square).expect("square is not on the floor")
+    }
+
+    #[inline]
+    fn square(&self, pos: Pos) -> Option<usize> {
+        let (x, y) = (pos.x as usize, pos.y as usize);
+        if x < self.width && y < self.height {
+            Some(x + 1 + (y + 1) * self.stride)
+        } else {
+            None
+        }
+    }
+
+    #[inline]
+    fn pos(&self, square: usize) -> Pos {
+        Pos {
+            x: (square % self.stride - 1) as u16,
+            y: (square / self.stride - 1) as u16,
+        }
+    }
+}
+
+/// Push of the box on square `from` to the neighbor square `to`.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub(crate) struct Push {
+    pub from: u32,
+    pub to: u32,
+}
+
+impl Push {
+    /// Square the player has to stand on to push.
+    #[inline]
+    pub fn player_square(&self) -> usize {
+        (2 * self.from - self.to) as usize
+    }
 }
 
 #[derive(Clone)]
 pub struct Map {
-    props: Rc<MapProps>,
+    props: Arc<MapProps>,
     solve_state: SolveState,
+    box_bits: BitBoard,
 }
 
 impl Map {
+    fn new(props: Arc<MapProps>, solve_state: SolveState) -> Self {
+        let mut box_bits = BitBoard::new(props.walls.len());
+        for b in solve_state.boxes.iter() {
+            box_bits.insert(props.floor.square(b));
+        }
+        Self {
+            props,
+            solve_state,
+            box_bits,
+        }
+    }
+
+    /// Squares which are neither walls nor boxes.
+    pub fn free_squares(&self) -> BitBoard {
+        !&(&self.props.walls | &self.box_bits)
+    }
+
+    /// Squares the player can walk to without pushing a box.
+    pub fn reachable(&self) -> BitBoard {
+        let mut start = BitBoard::new(self.props.walls.len());
+        start.insert(self.props.floor.square(self.solve_state.player));
+        start.flood_fill(&self.free_squares(), &self.props.offsets)
+    }
+
+    /// Index of `pos` in the squares of this map.
+    pub fn square(&self, pos: Pos) -> Option<usize> {
+        self.props.square(pos)
+    }
+
+    /// Position of a square index of this map.
+    pub fn pos(&self, square: usize) -> Pos {
+        self.props.pos(square)
+    }
+
+    /// Number of square indices including the padding.
+    pub fn squares(&self) -> usize {
+        self.props.walls.len()
+    }
+
+    /// Index offsets of the neighbors of a square in the order up, down, left, right.
+    pub fn offsets(&self) -> [isize; 4] {
+        self.props.offsets
+    }
+
+    #[inline]
+    pub fn is_free_square(&self, square: usize) -> bool {
+        !self.props.walls.contains(square) && !self.box_bits.contains(square)
+    }
+
+    #[inline]
+    pub fn is_wall_square(&self, square: usize) -> bool {
+        self.props.walls.contains(square)
+    }
+
+    #[inline]
+    pub fn is_dead_square(&self, square: usize) -> bool {
+        self.props.dead.contains(square)
+    }
+
+    #[inline]
+    pub fn is_box_square(&self, square: usize) -> bool {
+        self.box_bits.contains(square)
+    }
+
+    #[inline]
+    pub fn is_destination_square(&self, square: usize) -> bool {
+        self.props.destination_bits.contains(square)
+    }
+
     pub fn possible_moves(&self) -> Vec<Move> {
-        let mut moves = Vec::with_capacity(self.solve_state.boxes.len() * 4);
-        for b in self.solve_state.boxes.iter().copied() {
-            if self.is_free(b.up()) && self.is_push_target(b.down()) {
-                moves.push(Move::new(b, b.down()));
-            }
-            if self.is_push_target(b.up()) && self.is_free(b.down()) {
-                moves.push(Move::new(b, b.up()));
-            }
-            if self.is_push_target(b.left()) && self.is_free(b.right()) {
-                moves.push(Move::new(b, b.left()));
-            }
-            if self.is_free(b.left()) && self.is_push_target(b.right()) {
-                moves.push(Move::new(b, b.right()));
-            }
+        self.to_moves(self.pushes_from(&self.free_squares()))
+    }
+
+    /// Pushes the player can reach without pushing another box first.
+    pub fn reachable_moves(&self) -> Vec<Move> {
+        self.to_moves(self.pushes_from(&self.reachable()))
+    }
+
+    /// Pushes of boxes which are not blocked by walls, dead squares or other boxes.
+    pub(crate) fn possible_pushes(&self) -> Vec<Push> {
+        self.pushes_from(&self.free_squares())
+    }
+
+    /// Pushes of boxes where the player can stand on a square of `player` behind the box.
+    fn pushes_from(&self, player: &BitBoard) -> Vec<Push> {
+        let targets = !&(&self.props.dead | &self.box_bits);
+        let mut pushes = Vec::with_capacity(self.box_count() * 4);
+        for offset in self.props.offsets {
+            let mut pushable = &self.box_bits & &player.shifted(offset);
+            pushable &= &targets.shifted(-offset);
+            pushes.extend(pushable.iter().map(|square| Push {
+                from: square as u32,
+                to: square.wrapping_add_signed(offset) as u32,
+            }));
+        }
+        pushes
+    }
+
+    fn to_moves(&self, pushes: Vec<Push>) -> Vec<Move> {
+        pushes.into_iter().map(|push| self.to_move(push)).collect()
+    }
+
+    pub(crate) fn to_move(&self, push: Push) -> Move {
+        let offset = push.to as isize - push.from as isize;
+        let direction = self.props.offsets.iter().position(|o| *o == offset).unwrap();
+        Move {
+            start: self.pos(push.from as usize),
+            end: self.pos(push.to as usize),
+            direction: Direction::ALL[direction],
+        }
+    }
+
+    pub(crate) fn to_push(&self, m: Move) -> Push {
+        Push {
+            from: self.square(m.start).expect("move outside of map") as u32,
+            to: self.square(m.end).expect("move outside of map") as u32,
         }
-        moves
     }
 
     pub fn is_free(&self, pos: Pos) -> bool {
-        !self.is_wall(pos) && !self.solve_state.boxes.contains(&pos)
+        self.props
+            .square(pos)
+            .is_some_and(|square| self.is_free_square(square))
     }
 
     pub fn is_push_target(&self, pos: Pos) -> bool {
-        !self.is_dead(pos) && !self.solve_state.boxes.contains(&pos)
+        self.props
+            .square(pos)
+            .is_some_and(|square| !self.is_dead_square(square) && !self.is_box_square(square))
+    }
+
+    pub fn is_box(&self, pos: Pos) -> bool {
+        self.props
+            .square(pos)
+            .is_some_and(|square| self.is_box_square(square))
     }
 
+    /// Squares outside of the map count as walls.
     pub fn is_wall(&self, pos: Pos) -> bool {
         self.props
-            .map
-            .get(pos.x as usize, pos.y as usize)
-            .copied()
-            .unwrap_or_default()
+            .square(pos)
+            .is_none_or(|square| self.is_wall_square(square))
     }
 
+    /// Squares outside of the map count as dead.
     pub fn is_dead(&self, pos: Pos) -> bool {
         self.props
-            .dead
-            .get(pos.x as usize, pos.y as usize)
-            .copied()
-            .unwrap_or_default()
+            .square(pos)
+            .is_none_or(|square| self.is_dead_square(square))
     }
 
     pub fn is_destination(&self, pos: Pos) -> bool {
-        self.props.destinations.contains(&pos)
+        self.props
+            .square(pos)
+            .is_some_and(|square| self.is_destination_square(square))
+    }
+
+    /// Checks whether the box at `pos` is part of a group of boxes that can never be moved
+    /// again while at least one of them is not on a destination.
+    pub fn is_freeze_deadlock(&self, pos: Pos) -> bool {
+        self.props
+            .square(pos)
+            .is_some_and(|square| self.is_freeze_deadlock_square(square))
+    }
+
+    pub(crate) fn is_freeze_deadlock_square(&self, square: usize) -> bool {
+        let mut frozen = Vec::new();
+        if !self.is_frozen(square, &mut Vec::new(), &mut frozen) {
+            return false;
+        }
+        frozen.iter().any(|b| !self.is_destination_square(*b))
+    }
+
+    fn is_frozen(&self, square: usize, visited: &mut Vec<usize>, frozen: &mut Vec<usize>) -> bool {
+        let [up, down, left, right] = self.props.offsets;
+        // boxes found frozen while this box was assumed to be a wall
+        let mark = frozen.len();
+        visited.push(square);
+        let horizontal = self.is_axis_blocked(square, left, right, visited, frozen);
+        let vertical = horizontal && self.is_axis_blocked(square, up, down, visited, frozen);
+        visited.pop();
+        if vertical {
+            frozen.push(square);
+        } else {
+            frozen.truncate(mark);
+        }
+        vertical
+    }
+
+    fn is_axis_blocked(
+        &self,
+        square: usize,
+        offset_a: isize,
+        offset_b: isize,
+        visited: &mut Vec<usize>,
+        frozen: &mut Vec<usize>,
+    ) -> bool {
+        let a = square.wrapping_add_signed(offset_a);
+        let b = square.wrapping_add_signed(offset_b);
+        if self.is_wall_square(a)
+            || self.is_wall_square(b)
+            || visited.contains(&a)
+            || visited.contains(&b)
+        {
+            return true;
+        }
+        if self.is_dead_square(a) && self.is_dead_square(b) {
+            return true;
+        }
+        (self.is_box_square(a) && self.is_frozen(a, visited, frozen))
+            || (self.is_box_square(b) && self.is_frozen(b, visited, frozen))
     }
 
     pub fn apply_move(&mut self, m: Move) {
-        self.solve_state.apply_move(m);
-        self.solve_state.player = m.start;
+        self.apply_push(self.to_push(m));
+    }
+
+    pub(crate) fn apply_push(&mut self, push: Push) {
+        let from = self.props.floor_index(push.from as usize);
+        let to = self.props.floor_index(push.to as usize);
+        self.solve_state.apply_push(&self.props.floor, from, to);
+        self.box_bits.remove(push.from as usize);
+        self.box_bits.insert(push.to as usize);
     }
 
     pub fn is_solved(&self) -> bool {
@@ -210,16 +586,129 @@ impl Map {
         &self.solve_state
     }
 
-    pub fn destinations(&self) -> &[Pos] {
-        &self.props.destinations
+    pub fn destinations(&self) -> impl Iterator<Item = Pos> + '_ {
+        self.props
+            .destinations
+            .iter()
+            .map(|d| self.pos(self.props.floor.square(d)))
+    }
+
+    pub fn boxes(&self) -> impl Iterator<Item = Pos> + '_ {
+        self.solve_state
+            .boxes
+            .iter()
+            .map(|b| self.pos(self.props.floor.square(b)))
     }
 
-    pub fn boxes(&self) -> &[Pos] {
-        &self.solve_state.boxes
+    pub fn box_count(&self) -> usize {
+        self.solve_state.boxes.len()
     }
 
     pub fn player(&self) -> Pos {
-        self.solve_state.player
+        self.pos(self.props.floor.square(self.solve_state.player))
+    }
+
+    /// Transforms other than the identity which map the level onto itself.
+    pub fn symmetries(&self) -> impl Iterator<Item = Transform> + '_ {
+        self.props.symmetries.iter().map(|(t, _)| *t)
+    }
+
+    /// Smallest state of all states which are symmetric to the current one. Symmetric states
+    /// have the same solutions up to the symmetry.
+    pub fn canonical_solve_state(&self) -> Cow<'_, SolveState> {
+        let mut canonical = Cow::Borrowed(&self.solve_state);
+        for (_, permutation) in self.props.symmetries.iter() {
+            let mut boxes = BoxSet::new(self.props.floor.len());
+            for b in self.solve_state.boxes.iter() {
+                boxes.insert(permutation[b as usize]);
+            }
+            let player = permutation[self.solve_state.player as usize];
+            let state = SolveState::new(player, boxes, &self.props.floor);
+            if state < *canonical {
+                canonical = Cow::Owned(state);
+            }
+        }
+        canonical
+    }
+
+    /// Compact numbering of the floor squares of this level.
+    pub fn floor(&self) -> &Floor {
+        &self.props.floor
+    }
+
+    /// Sum of the distances of all boxes to their nearest destination.
+    pub fn destination_distance(&self) -> u32 {
+        self.solve_state
+            .boxes
+            .iter()
+            .map(|b| self.props.destination_distance[b as usize])
+            .sum()
+    }
+
+    /// Minimal sum of the push distances of all boxes when each box is pushed to a different
+    /// destination. `None` if there is no such assignment, then the level can not be solved.
+    pub fn matching_distance(&self) -> Option<u32> {
+        Matching::new(self).cost(self.push_distances())
+    }
+
+    /// Pushes to move a lone box from each floor square to each destination.
+    pub fn push_distances(&self) -> &PushDistances {
+        let props = &self.props;
+        props
+            .push_distances
+            .get_or_init(|| PushDistances::new(&props.floor, &props.destinations))
+    }
+
+    /// Minimal pushes to move a lone box from `from` to `destination`, wherever the player is.
+    pub fn push_distance(&self, from: Pos, destination: Pos) -> Option<u32> {
+        let (square, destination) = self.push_distance_indices(from, destination)?;
+        self.push_distances().simple(destination, square)
+    }
+
+    /// Minimal pushes to move a lone box from `from` to `destination` with the player starting
+    /// on `player`. Other boxes are ignored.
+    pub fn player_push_distance(&self, from: Pos, player: Pos, destination: Pos) -> Option<u32> {
+        let (square, destination) = self.push_distance_indices(from, destination)?;
+        let floor = &self.props.floor;
+        let player = floor.index(self.square(player)?)?;
+        if player == square {
+            return None;
+        }
+
+        // sides of the box the player can walk to
+        let mut region = BoxSet::new(floor.len());
+        region.insert(square);
+        region.insert(player);
+        let mut stack = vec![player];
+        while let Some(next) = stack.pop() {
+            for neighbor in floor.neighbors(next) {
+                if neighbor != NO_FLOOR && !region.contains(neighbor) {
+                    region.insert(neighbor);
+                    stack.push(neighbor);
+                }
+            }
+        }
+        floor
+            .neighbors(square)
+            .iter()
+            .enumerate()
+            .filter(|(_, neighbor)| **neighbor != NO_FLOOR && region.contains(**neighbor))
+            .filter_map(|(side, _)| {
+                self.push_distances()
+                    .with_player_side(destination, square, side)
+            })
+            .min()
+    }
+
+    /// Floor index of `from` and index of `destination` in `PushDistances`.
+    fn push_distance_indices(&self, from: Pos, destination: Pos) -> Option<(u32, usize)> {
+        let floor = &self.props.floor;
+        let square = floor.index(self.square(from)?)?;
+        let destination = floor.index(self.square(destination)?)?;
+        Some((
+            square,
+            self.push_distances().destination_index(destination)?,
+        ))
     }
 
     pub fn height(&self) -> usize {
@@ -234,9 +723,75 @@ impl Map {
         self.props.width * self.props.height
     }
 
+    /// Same level with another state.
+    pub(crate) fn with_solve_state(&self, solve_state: SolveState) -> Self {
+        Self::new(self.props.clone(), solve_state)
+    }
+
+    /// Same level with the player and the boxes at the given positions. The level properties
+    /// are shared with this map.
+    pub fn with_positions(
+        &self,
+        player: Pos,
+        boxes: impl IntoIterator<Item = Pos>,
+    ) -> Result<Self, StateError> {
+        Ok(self.with_solve_state(self.state_at(player, boxes)?))
+    }
+
+    /// Same level in `state`, which may come from another map of the same level.
+    pub fn with_checked_solve_state(&self, state: &SolveState) -> Result<Self, StateError> {
+        let floor = &self.props.floor;
+        if state.floor_len as usize != floor.len()
+            || state.player as usize >= floor.len()
+            || state.boxes.iter().any(|b| b as usize >= floor.len())
+        {
+            return Err(StateError::ForeignState);
+        }
+
+        let player = self.pos(floor.square(state.player));
+        let boxes = state.boxes.iter().map(|b| self.pos(floor.square(b)));
+        self.with_positions(player, boxes)
+    }
+
+    /// State of this level with the player and the boxes at the given positions.
+    pub fn state_at(
+        &self,
+        player: Pos,
+        boxes: impl IntoIterator<Item = Pos>,
+    ) -> Result<SolveState, StateError> {
+        let floor = &self.props.floor;
+        let floor_index = |pos: Pos| self.square(pos).and_then(|square| floor.index(square));
+
+        let mut box_set = BoxSet::new(floor.len());
+        for pos in boxes {
+            let b = floor_index(pos).ok_or(StateError::BoxOffFloor(pos))?;
+            if box_set.contains(b) {
+                return Err(StateError::DuplicateBox(pos));
+            }
+            box_set.insert(b);
+        }
+        let player_index = floor_index(player).ok_or(StateError::PlayerOffFloor(player))?;
+        if box_set.contains(player_index) {
+            return Err(StateError::PlayerOnBox(player));
+        }
+        let destinations = self.props.destinations.len();
+        if box_set.len() != destinations {
+            return Err(StateError::BoxCount {
+                boxes: box_set.len(),
+                destinations,
+            });
+        }
+
+        Ok(SolveState::new(player_index, box_set, floor))
+    }
+
+    pub(crate) fn into_solve_state(self) -> SolveState {
+        self.solve_state
+    }
+
     pub fn set_player_pos(&mut self, pos: Pos) {
-        // TODO: remove me
-        self.solve_state.player = pos;
+        let square = self.square(pos).expect("player outside of map");
+        self.solve_state.player = self.props.floor_index(square);
     }
 
     fn detect_dead_positions(input: &Input, width: usize, height: usize) -> Matrix<bool> {
@@ -349,79 +904,111 @@ impl Map {
     }
 }
 
+impl From<&Map> for Input {
+    /// Level in the current state of `map`.
+    fn from(map: &Map) -> Self {
+        let input = (0..map.height())
+            .map(|y| {
+                (0..map.width())
+                    .map(|x| {
+                        let pos = Pos::new(x as u16, y as u16);
+                        let destination = map.is_destination(pos);
+                        if map.is_wall(pos) {
+                            CellState::Wall
+                        } else if map.is_box(pos) {
+                            if destination {
+                                CellState::BoxOnDestination
+                            } else {
+                                CellState::Box
+                            }
+                        } else if map.player() == pos {
+                            if destination {
+                                CellState::PlayerOnDestination
+                            } else {
+                                CellState::Player
+                            }
+                        } else if destination {
+                            CellState::Destination
+                        } else {
+                            CellState::Empty
+                        }
+                    })
+                    .collect()
+            })
+            .collect();
+        Input { input }
+    }
+}
+
 impl From<Input> for Map {
     fn from(input: Input) -> Self {
-        let width = input
-            .input
-            .iter()
-            .map(|x| x.len())
-            .max()
-            .unwrap_or_default();
-        let height = input.input.len();
-
-        let mut map = Matrix::fill(false, width, height);
+        // dead square detection looks at neighbors in other lines
+        let input = input.padded();
+        let width = input.width();
+        let height = input.height();
+
+        let stride = width + 2;
+        let len = stride * (height + 2);
+        let square = |x: usize, y: usize| x + 1 + (y + 1) * stride;
+
+        let mut walls = BitBoard::full(len);
+        for y in 0..height {
+            for x in 0..width {
+                walls.remove(square(x, y));
+            }
+        }
         for (y, line) in input.input.iter().enumerate() {
             for (x, cell) in line.iter().enumerate() {
-                map[(x, y)] = cell.is_wall();
+                if cell.is_wall() {
+                    walls.insert(square(x, y));
+                }
             }
         }
 
-        let dead = Self::detect_dead_positions(&input, width, height);
-
-        let mut boxes = input.get_boxes();
-        boxes.sort();
-
-        let mut destinations = input.get_destinations();
-        destinations.sort();
+        let dead_matrix = Self::detect_dead_positions(&input, width, height);
+        let mut dead = walls.clone();
+        for y in 0..height {
+            for x in 0..width {
+                if dead_matrix[(x, y)] {
+                    dead.insert(square(x, y));
+                }
+            }
+        }
 
-        Map {
-            props: Rc::new(MapProps {
-                width,
-                height,
-                map,
-                dead,
-                destinations,
-            }),
-            solve_state: SolveState {
-                player: input.get_player_pos().unwrap(),
-                boxes,
-            },
+        let player = input.get_player_pos().unwrap();
+        let props = MapProps::new(
+            width,
+            height,
+            walls,
+            dead,
+            player,
+            &input.get_boxes(),
+            &input.get_destinations(),
+        );
+
+        let mut boxes = BoxSet::new(props.floor.len());
+        for b in input.get_boxes() {
+            boxes.insert(props.floor_index(props.square(b).unwrap()));
         }
+        let player = props.floor_index(props.square(player).unwrap());
+        let solve_state = SolveState::new(player, boxes, &props.floor);
+
+        Map::new(Arc::new(props), solve_state)
     }
 }
 
 impl fmt::Display for Map {
+    /// Writes the level in the current state in XSB format, see `render::DebugRenderer` for
+    /// debug views.
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        for y in 0..self.props.height {
-            for x in 0..self.props.width {
-                let pos = Pos {
-                    x: x as u8,
-                    y: y as u8,
-                };
-                if self.props.map[(x, y)] {
-                    f.write_char('#')?;
-                } else if self.props.dead[(x, y)] {
-                    f.write_char('~')?;
-                } else if self.solve_state.player == pos {
-                    f.write_char('@')?;
-                } else if self.solve_state.boxes.contains(&pos) {
-                    f.write_char('o')?;
-                } else if self.props.destinations.contains(&pos) {
-                    f.write_char('.')?;
-                } else {
-                    f.write_char(' ')?;
-                }
-            }
-            f.write_char('\n')?;
-        }
-        Ok(())
+        Input::from(self).fmt(f)
     }
 }
 
 #[derive(Copy, Clone, Eq, PartialEq, Debug, Ord, PartialOrd, Hash)]
 pub struct Pos {
-    pub x: u8,
-    pub y: u8,
+    pub x: u16,
+    pub y: u16,
 }
 
 impl fmt::Display for Pos {
@@ -430,25 +1017,94 @@ impl fmt::Display for Pos {
     }
 }
 
+/// Direction on the screen: rows of the level text go down, columns go right.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash)]
+pub enum Direction {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+impl Direction {
+    /// All directions in the order of `Map::offsets`.
+    pub const ALL: [Direction; 4] = [
+        Direction::Up,
+        Direction::Down,
+        Direction::Left,
+        Direction::Right,
+    ];
+
+    /// Position of this direction in `Direction::ALL`.
+    pub fn index(self) -> usize {
+        self as usize
+    }
+
+    pub fn reverse(self) -> Self {
+        match self {
+            Direction::Up => Direction::Down,
+            Direction::Down => Direction::Up,
+            Direction::Left => Direction::Right,
+            Direction::Right => Direction::Left,
+        }
+    }
+
+    /// Character in LURD notation: lower case for walking, upper case for pushing.
+    pub fn to_lurd(self, push: bool) -> char {
+        let c = match self {
+            Direction::Up => 'u',
+            Direction::Down => 'd',
+            Direction::Left => 'l',
+            Direction::Right => 'r',
+        };
+        if push {
+            c.to_ascii_uppercase()
+        } else {
+            c
+        }
+    }
+
+    pub fn from_lurd(c: char) -> Option<(Self, bool)> {
+        let direction = match c.to_ascii_lowercase() {
+            'u' => Direction::Up,
+            'd' => Direction::Down,
+            'l' => Direction::Left,
+            'r' => Direction::Right,
+            _ => return None,
+        };
+        Some((direction, c.is_ascii_uppercase()))
+    }
+}
+
 impl Pos {
-    pub fn new(x: u8, y: u8) -> Self {
+    pub fn new(x: u16, y: u16) -> Self {
         Self { x, y }
     }
 
+    /// Neighbor above on the screen (`y - 1`).
+    ///
+    /// Panics on underflow in debug builds, use `checked_up` at the edge of the map.
     pub fn up(&self) -> Self {
         Self {
             x: self.x,
-            y: self.y + 1,
+            y: self.y - 1,
         }
     }
 
+    /// Neighbor below on the screen (`y + 1`).
+    ///
+    /// Panics on overflow in debug builds, use `checked_down` near the end of the coordinate
+    /// range.
     pub fn down(&self) -> Self {
         Self {
             x: self.x,
-            y: self.y - 1,
+            y: self.y + 1,
         }
     }
 
+    /// Neighbor with `x - 1`.
+    ///
+    /// Panics on underflow in debug builds, use `checked_left` at the edge of the map.
     pub fn left(&self) -> Self {
         Self {
             x: self.x - 1,
@@ -456,34 +1112,107 @@ impl Pos {
         }
     }
 
+    /// Neighbor with `x + 1`.
+    ///
+    /// Panics on overflow in debug builds, use `checked_right` near the end of the coordinate
+    /// range.
     pub fn right(&self) -> Self {
         Self {
             x: self.x + 1,
             y: self.y,
         }
     }
+
+    /// Neighbor above on the screen (`y - 1`), if it is representable.
+    pub fn checked_up(&self) -> Option<Self> {
+        self.y.checked_sub(1).map(|y| Self { x: self.x, y })
+    }
+
+    /// Neighbor below on the screen (`y + 1`), if it is representable.
+    pub fn checked_down(&self) -> Option<Self> {
+        self.y.checked_add(1).map(|y| Self { x: self.x, y })
+    }
+
+    /// Neighbor with `x - 1`, if it is representable.
+    pub fn checked_left(&self) -> Option<Self> {
+        self.x.checked_sub(1).map(|x| Self { x, y: self.y })
+    }
+
+    /// Neighbor with `x + 1`, if it is representable.
+    pub fn checked_right(&self) -> Option<Self> {
+        self.x.checked_add(1).map(|x| Self { x, y: self.y })
+    }
+
+    /// Neighbor in `direction`, if it is representable.
+    pub fn step(&self, direction: Direction) -> Option<Self> {
+        match direction {
+            Direction::Up => self.checked_up(),
+            Direction::Down => self.checked_down(),
+            Direction::Left => self.checked_left(),
+            Direction::Right => self.checked_right(),
+        }
+    }
+
+    /// Direction of the neighbor `other`, if it is one.
+    pub fn direction_to(&self, other: Pos) -> Option<Direction> {
+        Direction::ALL
+            .into_iter()
+            .find(|d| self.step(*d) == Some(other))
+    }
+
+    /// All representable neighbors in the order up, down, left, right.
+    pub fn neighbors(&self) -> impl Iterator<Item = Pos> {
+        let pos = *self;
+        Direction::ALL.into_iter().filter_map(move |d| pos.step(d))
+    }
 }
 
+/// Push of the box on `start` to `end`.
 #[derive(Copy, Clone, Eq, PartialEq, Debug)]
 pub struct Move {
     pub start: Pos,
     pub end: Pos,
+    pub direction: Direction,
 }
 
 impl Move {
+    /// Push from `start` to the neighbor `end`.
+    ///
+    /// Panics if `end` is not a neighbor of `start`.
     pub fn new(start: Pos, end: Pos) -> Self {
-        Self { start, end }
+        let direction = start
+            .direction_to(end)
+            .expect("start and end of move are not neighbors");
+        Self {
+            start,
+            end,
+            direction,
+        }
+    }
+
+    /// Push of the box on `start` in `direction`, if the target is representable.
+    pub fn in_direction(start: Pos, direction: Direction) -> Option<Self> {
+        start.step(direction).map(|end| Self {
+            start,
+            end,
+            direction,
+        })
+    }
+
+    /// Position the player has to stand on to push.
+    pub fn player_pos(&self) -> Option<Pos> {
+        self.start.step(self.direction.reverse())
     }
 }
 
 #[derive(Copy, Clone, PartialEq, Ord, PartialOrd, Eq, Debug)]
 pub struct Costs {
-    pub pushes: u16,
-    pub moves: u16,
+    pub pushes: u32,
+    pub moves: u32,
 }
 
 impl Costs {
-    pub fn new(pushes: u16, moves: u16) -> Self {
+    pub fn new(pushes: u32, moves: u32) -> Self {
         Self { pushes, moves }
     }
 
@@ -510,24 +1239,111 @@ impl Solution {
     pub fn moves(&self) -> &[Move] {
         &self.moves
     }
+
+    /// Number of maximal straight runs of pushes of the same box.
+    pub fn box_lines(&self) -> usize {
+        self.moves
+            .iter()
+            .enumerate()
+            .filter(|(i, m)| {
+                *i == 0 || {
+                    let last = self.moves[i - 1];
+                    last.end != m.start || last.direction != m.direction
+                }
+            })
+            .count()
+    }
+
+    /// Number of times another box than before is pushed, counting the first push.
+    pub fn box_changes(&self) -> usize {
+        self.moves
+            .iter()
+            .enumerate()
+            .filter(|(i, m)| *i == 0 || self.moves[i - 1].end != m.start)
+            .count()
+    }
+
+    /// Number of maximal straight runs of steps of the player, walking or pushing, with the
+    /// shortest walks between the pushes. `None` if the solution does not belong to `map`.
+    pub fn player_lines(&self, map: &Map) -> Option<usize> {
+        let lurd = self.to_lurd(map)?;
+        let mut lines = 0;
+        let mut last = None;
+        for c in lurd.chars() {
+            let direction = c.to_ascii_lowercase();
+            if last != Some(direction) {
+                lines += 1;
+            }
+            last = Some(direction);
+        }
+        Some(lines)
+    }
 }
 
-#[derive(Eq, PartialEq, Hash, Clone)]
+/// Position of the player and all boxes as indices of the floor of the map this state
+/// belongs to.
+#[derive(Eq, PartialEq, Clone, Debug)]
 pub struct SolveState {
-    player: Pos,
-    boxes: Vec<Pos>,
+    player: u32,
+    boxes: BoxSet,
+    /// Zobrist hash of `boxes`.
+    hash: u64,
+    /// Number of floor squares of the level, to recognize states of other levels.
+    floor_len: u32,
+}
+
+impl Ord for SolveState {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        (&self.boxes, self.player).cmp(&(&other.boxes, other.player))
+    }
+}
+
+impl PartialOrd for SolveState {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Hash for SolveState {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_u64(self.hash ^ (self.player as u64).wrapping_mul(0x9e37_79b9_7f4a_7c15));
+    }
 }
 
 impl SolveState {
-    pub fn apply_move(&mut self, m: Move) {
-        let b = self
-            .boxes
-            .iter_mut()
-            .find(|b| b == &&m.start)
-            .expect("impossible move: box does not exist");
-        *b = m.end;
+    fn new(player: u32, boxes: BoxSet, floor: &Floor) -> Self {
+        let hash = boxes.iter().fold(0, |hash, b| hash ^ floor.zobrist(b));
+        Self {
+            player,
+            boxes,
+            hash,
+            floor_len: floor.len() as u32,
+        }
+    }
+
+    /// Floor index of the player.
+    pub fn player(&self) -> u32 {
+        self.player
+    }
+
+    /// Floor indices of all boxes.
+    pub fn boxes(&self) -> &BoxSet {
+        &self.boxes
+    }
+
+    /// Bytes used by the box set on the heap.
+    pub fn heap_size(&self) -> usize {
+        self.boxes.heap_size()
+    }
 
-        self.player = m.start;
-        self.boxes.sort();
+    fn apply_push(&mut self, floor: &Floor, from: u32, to: u32) {
+        assert!(
+            self.boxes.contains(from),
+            "impossible move: box does not exist"
+        );
+        self.boxes.remove(from);
+        self.boxes.insert(to);
+        self.hash ^= floor.zobrist(from) ^ floor.zobrist(to);
+        self.player = from;
     }
 }
@@ -1,39 +1,213 @@
-use solve_sokoban::algos::dijkstra::{shortest_path, PathGraph};
+use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use crossterm::terminal::{self, Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen};
+use crossterm::{cursor, execute, queue};
+use solve_sokoban::game::Game;
+use solve_sokoban::generator::{Generator, GeneratorConfig};
+use solve_sokoban::render::AnsiRenderer;
 use solve_sokoban::solver::Solver;
-use solve_sokoban::{Costs, Input, Map, Pos};
+use solve_sokoban::{Direction, Input, Map};
+use std::fmt::Write as _;
+use std::io::Write;
 use std::str::FromStr;
+use std::time::Duration;
+use std::{env, fs, io, process};
+
+const USAGE: &str = "usage: solve-sokoban [--play | --replay] [--delay MS] [--no-color] [LEVEL]
+       solve-sokoban --generate SEED";
+
+const PLAY_KEYS: &str = "arrows: move  u: undo  r: redo  s: restart  h: hint  q: quit";
+
+/// Time the solver may spend on a hint.
+const HINT_TIMEOUT: Duration = Duration::from_secs(10);
+
+struct Args {
+    level: Option<String>,
+    generate: Option<u64>,
+    play: bool,
+    replay: bool,
+    delay: Duration,
+    colors: bool,
+}
+
+fn parse_args() -> Result<Args, String> {
+    let mut result = Args {
+        level: None,
+        generate: None,
+        play: false,
+        replay: false,
+        delay: Duration::from_millis(100),
+        colors: true,
+    };
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--play" => result.play = true,
+            "--generate" => {
+                let seed = args.next().ok_or("missing value of --generate")?;
+                let seed = seed
+                    .parse()
+                    .map_err(|_| format!("invalid seed: {}", seed))?;
+                result.generate = Some(seed);
+            }
+            "--replay" => result.replay = true,
+            "--no-color" => result.colors = false,
+            "--delay" => {
+                let ms = args.next().ok_or("missing value of --delay")?;
+                let ms = ms.parse().map_err(|_| format!("invalid delay: {}", ms))?;
+                result.delay = Duration::from_millis(ms);
+            }
+            "-h" | "--help" => return Err(USAGE.to_string()),
+            _ if result.level.is_none() && !arg.starts_with('-') => result.level = Some(arg),
+            _ => return Err(format!("unexpected argument: {}\n{}", arg, USAGE)),
+        }
+    }
+    Ok(result)
+}
 
 fn main() {
-    let input = Input::from_str(include_str!("../tests/ttac2021/level2.txt"));
-    // let input = Input::from_str(include_str!("../tests/xsokoban/screen.1"));
-    let map = Map::from(input.unwrap());
-
-    println!("Map:\n{}", map);
-    if let Some(solution) = Solver::new().solve(&map) {
-        println!("{:?}", solution.costs());
-
-        let mut costs = Costs::zero();
-        let mut map = map.clone();
-        for (i, mv) in solution.moves().iter().enumerate() {
-            let push_player_pos = Pos {
-                x: 2 * mv.start.x - mv.end.x,
-                y: 2 * mv.start.y - mv.end.y,
-            };
-
-            costs.moves +=
-                shortest_path(&map, &PathGraph::new(&map), map.player(), push_player_pos).unwrap();
-
-            costs.pushes += 1;
-            costs.moves += 1;
-
-            println!();
-            println!("Step {}:", i + 1);
-
-            map.set_player_pos(push_player_pos);
-            println!("{}", map);
-            map.apply_move(*mv);
-            println!("{}", map);
-            println!("{:?}", costs);
+    let args = parse_args().unwrap_or_else(|err| {
+        eprintln!("{}", err);
+        process::exit(2);
+    });
+
+    if let Some(seed) = args.generate {
+        generate(seed);
+        return;
+    }
+
+    let level = match &args.level {
+        Some(path) => fs::read_to_string(path).unwrap_or_else(|err| {
+            eprintln!("{}: {}", path, err);
+            process::exit(1);
+        }),
+        None => include_str!("../tests/ttac2021/level2.txt").to_string(),
+    };
+    let map = match Input::from_str(&level) {
+        Ok(input) => Map::from(input),
+        Err(err) => {
+            eprintln!("invalid level: {:?}", err);
+            process::exit(1);
+        }
+    };
+    let renderer = AnsiRenderer::new()
+        .with_colors(args.colors)
+        .with_delay(args.delay);
+
+    if args.play {
+        play(map, &renderer).expect("terminal error");
+        return;
+    }
+
+    println!("Map:\n{}", renderer.render(&map));
+    let solution = match Solver::new().solve(&map) {
+        Some(solution) => solution,
+        None => {
+            println!("No solution");
+            process::exit(1);
         }
+    };
+    let lurd = solution.to_lurd(&map).expect("solution of another level");
+    println!("{:?}", solution.costs());
+    println!("{}", lurd);
+
+    if args.replay {
+        renderer
+            .replay(&map, &lurd, &mut io::stdout().lock())
+            .expect("replay failed");
+        return;
+    }
+
+    let mut map = map.clone();
+    for (i, m) in solution.moves().iter().enumerate() {
+        map.apply_move(*m);
+        println!();
+        println!("Push {} ({}):", i + 1, m.direction.to_lurd(true));
+        print!("{}", renderer.render(&map));
+    }
+}
+
+/// Prints a random level generated from `seed`.
+fn generate(seed: u64) {
+    let config = GeneratorConfig {
+        seed,
+        ..GeneratorConfig::default()
+    };
+    match Generator::new(config).generate() {
+        Some(level) => {
+            println!("{}", level.xsb());
+            println!("{:?}", level.score);
+        }
+        None => {
+            eprintln!("no level found");
+            process::exit(1);
+        }
+    }
+}
+
+/// Lets the user play `map` in the terminal.
+fn play(map: Map, renderer: &AnsiRenderer) -> io::Result<()> {
+    let mut out = io::stdout().lock();
+    terminal::enable_raw_mode()?;
+    execute!(out, EnterAlternateScreen, cursor::Hide)?;
+    let result = play_loop(&mut Game::new(map), renderer, &mut out);
+    execute!(out, cursor::Show, LeaveAlternateScreen)?;
+    terminal::disable_raw_mode()?;
+    result
+}
+
+fn play_loop(game: &mut Game, renderer: &AnsiRenderer, out: &mut impl Write) -> io::Result<()> {
+    let mut message = String::new();
+    loop {
+        let costs = game.costs();
+        let deadlocks = game.deadlocks();
+        let mut screen = renderer.render(game.map());
+        writeln!(screen, "moves: {}, pushes: {}", costs.moves, costs.pushes).unwrap();
+        if game.is_solved() {
+            writeln!(screen, "Solved! {}", game.lurd()).unwrap();
+        } else if !deadlocks.is_empty() {
+            let boxes: Vec<_> = deadlocks.iter().map(|pos| pos.to_string()).collect();
+            writeln!(screen, "Deadlock: boxes at {}", boxes.join(", ")).unwrap();
+        }
+        writeln!(screen, "{}", message).unwrap();
+        writeln!(screen, "{}", PLAY_KEYS).unwrap();
+        // raw mode does not return the cursor to the start of the line
+        queue!(out, Clear(ClearType::All), cursor::MoveTo(0, 0))?;
+        write!(out, "{}", screen.replace('\n', "\r\n"))?;
+        out.flush()?;
+
+        let key = match event::read()? {
+            Event::Key(key) if key.kind == KeyEventKind::Press => key,
+            _ => continue,
+        };
+        message.clear();
+        let direction = match key.code {
+            KeyCode::Up => Direction::Up,
+            KeyCode::Down => Direction::Down,
+            KeyCode::Left => Direction::Left,
+            KeyCode::Right => Direction::Right,
+            KeyCode::Char('u') => {
+                game.undo();
+                continue;
+            }
+            KeyCode::Char('r') => {
+                game.redo();
+                continue;
+            }
+            KeyCode::Char('s') => {
+                game.restart();
+                continue;
+            }
+            KeyCode::Char('h') => {
+                message = match game.hint(HINT_TIMEOUT) {
+                    Some(m) => format!("Hint: push the box at {} {:?}", m.start, m.direction),
+                    None => "Hint: no solution found".to_string(),
+                };
+                continue;
+            }
+            KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => return Ok(()),
+            _ => continue,
+        };
+        game.step(direction);
     }
 }
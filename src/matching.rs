@@ -0,0 +1,156 @@
+//! Minimal cost assignment of boxes to destinations.
+
+use crate::pushdistance::PushDistances;
+use crate::Map;
+
+/// Cost of assigning a box to a destination it can not be pushed to. Larger than the cost of
+/// any assignment of reachable destinations.
+const INFINITE: i64 = 1 << 40;
+
+/// Assignment of each box to a different destination with the minimal sum of push distances,
+/// found with the Hungarian algorithm. Rows are boxes, columns are destinations, both counted
+/// from 1 with 0 as the free row and column of the usual formulation. With more destinations
+/// than boxes, rows of dummy boxes with zero costs make the problem square.
+///
+/// A push moves only one box, so only the row of that box changes. `move_box` keeps the
+/// potentials of the other rows and repairs the assignment with a single augmenting path. This
+/// is only exact when every column is assigned, which the dummy rows ensure.
+#[derive(Clone)]
+pub(crate) struct Matching {
+    /// Floor index of the box of each row, without the dummy rows.
+    boxes: Vec<u32>,
+    columns: usize,
+    row_potential: Vec<i64>,
+    column_potential: Vec<i64>,
+    /// Row assigned to each column, 0 for none.
+    assigned: Vec<usize>,
+}
+
+impl Matching {
+    /// Matching of the boxes of `map`.
+    pub fn new(map: &Map) -> Self {
+        let distances = map.push_distances();
+        let boxes: Vec<u32> = std::iter::once(0)
+            .chain(map.solve_state().boxes.iter())
+            .collect();
+        let columns = distances.destinations().len();
+        let mut matching = Self {
+            row_potential: vec![0; boxes.len().max(columns + 1)],
+            column_potential: vec![0; columns + 1],
+            assigned: vec![0; columns + 1],
+            boxes,
+            columns,
+        };
+        if matching.boxes() <= columns {
+            for row in 1..=columns {
+                matching.augment(distances, row);
+            }
+        }
+        matching
+    }
+
+    /// Minimal sum of push distances, `None` if the boxes can not be pushed to different
+    /// destinations.
+    pub fn cost(&self, distances: &PushDistances) -> Option<u32> {
+        if self.boxes() > self.columns {
+            return None;
+        }
+        let mut sum = 0;
+        for column in 1..=self.columns {
+            let row = self.assigned[column];
+            if row <= self.boxes() {
+                let cost = self.cost_of(distances, row, column);
+                if cost >= INFINITE {
+                    return None;
+                }
+                sum += cost;
+            }
+        }
+        Some(sum as u32)
+    }
+
+    /// Moves the box on floor index `from` to `to`.
+    pub fn move_box(&mut self, distances: &PushDistances, from: u32, to: u32) {
+        let row = match self.boxes[1..].iter().position(|b| *b == from) {
+            Some(i) => i + 1,
+            None => return,
+        };
+        self.boxes[row] = to;
+        if self.boxes() > self.columns {
+            return;
+        }
+        if let Some(column) = self.assigned[1..].iter().position(|r| *r == row) {
+            self.assigned[column + 1] = 0;
+        }
+        // keep the reduced costs of the new row non-negative
+        self.row_potential[row] = (1..=self.columns)
+            .map(|column| self.cost_of(distances, row, column) - self.column_potential[column])
+            .min()
+            .unwrap_or_default();
+        self.augment(distances, row);
+    }
+
+    /// Number of rows of real boxes.
+    fn boxes(&self) -> usize {
+        self.boxes.len() - 1
+    }
+
+    fn cost_of(&self, distances: &PushDistances, row: usize, column: usize) -> i64 {
+        match self.boxes.get(row) {
+            Some(b) => distances.simple(column - 1, *b).map_or(INFINITE, i64::from),
+            None => 0,
+        }
+    }
+
+    /// Assigns the free `row` along a shortest augmenting path of reduced costs.
+    fn augment(&mut self, distances: &PushDistances, row: usize) {
+        let columns = self.columns;
+        let mut min_reduced = vec![i64::MAX; columns + 1];
+        let mut previous = vec![0; columns + 1];
+        let mut used = vec![false; columns + 1];
+
+        self.assigned[0] = row;
+        let mut column = 0;
+        loop {
+            used[column] = true;
+            let current = self.assigned[column];
+            let mut delta = i64::MAX;
+            let mut next = 0;
+            for j in 1..=columns {
+                if used[j] {
+                    continue;
+                }
+                let reduced = self.cost_of(distances, current, j)
+                    - self.row_potential[current]
+                    - self.column_potential[j];
+                if reduced < min_reduced[j] {
+                    min_reduced[j] = reduced;
+                    previous[j] = column;
+                }
+                if min_reduced[j] < delta {
+                    delta = min_reduced[j];
+                    next = j;
+                }
+            }
+            for j in 0..=columns {
+                if used[j] {
+                    self.row_potential[self.assigned[j]] += delta;
+                    self.column_potential[j] -= delta;
+                } else {
+                    min_reduced[j] -= delta;
+                }
+            }
+            column = next;
+            if self.assigned[column] == 0 {
+                break;
+            }
+        }
+        // flip the assignments along the path
+        while column != 0 {
+            let before = previous[column];
+            self.assigned[column] = self.assigned[before];
+            column = before;
+        }
+        self.assigned[0] = 0;
+    }
+}
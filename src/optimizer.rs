@@ -0,0 +1,163 @@
+//! Improves existing solutions by re-solving short segments of them.
+
+use std::time::{Duration, Instant};
+
+use crate::algos::bfs::DistanceMap;
+use crate::solver::{Solver, SolverConfig, Strategy};
+use crate::{CellState, Costs, Input, Map, Move, Pos, Solution};
+
+/// Result of `Optimizer::optimize`.
+#[derive(Debug)]
+pub struct Optimized {
+    pub solution: Solution,
+    /// Costs of the original pushes.
+    pub before: Costs,
+}
+
+impl Optimized {
+    /// Saved pushes and moves. Saved moves are negative if fewer pushes need more walking.
+    pub fn improvement(&self) -> (i64, i64) {
+        let after = self.solution.costs();
+        (
+            i64::from(self.before.pushes) - i64::from(after.pushes),
+            i64::from(self.before.moves) - i64::from(after.moves),
+        )
+    }
+}
+
+/// Shortens solutions without searching for an optimal solution of the whole level.
+///
+/// Every segment of up to `window` pushes is solved again optimally, from the state at its
+/// start to the boxes at its end. A shorter segment replaces the original one when the whole
+/// solution stays valid and gets cheaper. This is repeated until no segment improves.
+pub struct Optimizer {
+    window: usize,
+    segment_timeout: Duration,
+}
+
+impl Default for Optimizer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Optimizer {
+    pub fn new() -> Self {
+        Self {
+            window: 8,
+            segment_timeout: Duration::from_secs(1),
+        }
+    }
+
+    /// Maximal pushes of a segment which is solved again.
+    pub fn with_window(mut self, window: usize) -> Self {
+        self.window = window.max(1);
+        self
+    }
+
+    /// Time the solver may spend on each segment.
+    pub fn with_segment_timeout(mut self, timeout: Duration) -> Self {
+        self.segment_timeout = timeout;
+        self
+    }
+
+    /// Improves the pushes `moves` which solve `map`. Returns `None` if they do not solve it.
+    pub fn optimize(&self, map: &Map, moves: &[Move]) -> Option<Optimized> {
+        let before = Self::costs(map, moves)?;
+        let mut moves = moves.to_vec();
+        let mut costs = before;
+
+        let mut start = 0;
+        while start < moves.len() {
+            let end = (start + self.window).min(moves.len());
+            match self.improve_segment(map, &moves, start, end, costs) {
+                Some((better, better_costs)) => {
+                    moves = better;
+                    costs = better_costs;
+                    // earlier segments may improve now as well
+                    start = start.saturating_sub(self.window);
+                }
+                None => start += 1,
+            }
+        }
+
+        Some(Optimized {
+            solution: Solution::new(moves, costs),
+            before,
+        })
+    }
+
+    fn improve_segment(
+        &self,
+        map: &Map,
+        moves: &[Move],
+        start: usize,
+        end: usize,
+        costs: Costs,
+    ) -> Option<(Vec<Move>, Costs)> {
+        let mut from = map.clone();
+        for m in &moves[..start] {
+            from.apply_move(*m);
+        }
+        let mut to = from.clone();
+        for m in &moves[start..end] {
+            to.apply_move(*m);
+        }
+
+        let target = Self::with_destinations(&from, to.boxes());
+        let config = SolverConfig {
+            strategy: Strategy::UniformCost,
+            freeze_deadlocks: true,
+            deadline: Some(Instant::now() + self.segment_timeout),
+            ..SolverConfig::default()
+        };
+        let (segment, _) = Solver::with_config(config).solve_with_stats(&target);
+
+        let mut candidate = moves[..start].to_vec();
+        candidate.extend_from_slice(segment?.moves());
+        candidate.extend_from_slice(&moves[end..]);
+        let candidate_costs = Self::costs(map, &candidate)?;
+        (candidate_costs < costs).then_some((candidate, candidate_costs))
+    }
+
+    /// The state of `map` with the destinations replaced by `destinations`.
+    fn with_destinations(map: &Map, destinations: impl Iterator<Item = Pos>) -> Map {
+        let mut input = Input::from(map);
+        for cell in input.input.iter_mut().flatten() {
+            *cell = match *cell {
+                CellState::Destination => CellState::Empty,
+                CellState::BoxOnDestination => CellState::Box,
+                CellState::PlayerOnDestination => CellState::Player,
+                other => other,
+            };
+        }
+        for pos in destinations {
+            let cell = &mut input.input[pos.y as usize][pos.x as usize];
+            *cell = match *cell {
+                CellState::Box => CellState::BoxOnDestination,
+                CellState::Player => CellState::PlayerOnDestination,
+                _ => CellState::Destination,
+            };
+        }
+        Map::from(input)
+    }
+
+    /// Costs of the pushes `moves` with the shortest walks between them, `None` if they are
+    /// not possible or do not solve `map`.
+    fn costs(map: &Map, moves: &[Move]) -> Option<Costs> {
+        let mut map = map.clone();
+        let mut distances = DistanceMap::new();
+        let mut costs = Costs::zero();
+        for m in moves {
+            if !map.is_box(m.start) || !map.is_free(m.end) {
+                return None;
+            }
+            distances.update(&map);
+            let walk = distances.distance_to(&map, m.player_pos()?)?;
+            costs.moves += walk + 1;
+            costs.pushes += 1;
+            map.apply_move(*m);
+        }
+        map.is_solved().then_some(costs)
+    }
+}
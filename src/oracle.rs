@@ -0,0 +1,124 @@
+//! Repeated solvability queries on one level.
+
+use std::time::{Duration, Instant};
+
+use crate::solver::{Solver, SolverConfig, SolverStats, Strategy};
+use crate::{Costs, Map, Solution, SolveState, StateError};
+
+/// Answer of the oracle for one state.
+#[derive(Debug)]
+pub enum Answer {
+    /// Optimal solution from the state.
+    Solvable(Solution),
+    Unsolvable,
+    /// The search was stopped by the timeout.
+    Unknown,
+}
+
+/// Answers whether states of one level are solvable and what their optimal remaining costs
+/// are. Unlike `Solver`, it keeps what it learned between queries: the rest of every optimal
+/// solution found, states from which the level can not be solved and the buffers of the
+/// search.
+pub struct Oracle {
+    map: Map,
+    solver: Solver,
+    timeout: Option<Duration>,
+    stats: SolverStats,
+}
+
+impl Oracle {
+    /// Oracle for the level of `map`, finding push optimal solutions with minimal moves.
+    pub fn new(map: &Map) -> Self {
+        Self::with_strategy(map, Strategy::UniformCost)
+    }
+
+    /// Oracle using an optimal `strategy`.
+    ///
+    /// # Panics
+    ///
+    /// `Strategy::Greedy`, `Strategy::Reverse` and `Strategy::Bidirectional` do not find optimal
+    /// solutions and are not supported.
+    pub fn with_strategy(map: &Map, strategy: Strategy) -> Self {
+        assert!(
+            !matches!(
+                strategy,
+                Strategy::Greedy | Strategy::Reverse | Strategy::Bidirectional
+            ),
+            "oracle needs an optimal strategy"
+        );
+        let config = SolverConfig {
+            strategy,
+            freeze_deadlocks: true,
+            ..SolverConfig::default()
+        };
+        Self {
+            map: map.clone(),
+            solver: Solver::with_knowledge(config),
+            timeout: None,
+            stats: SolverStats::default(),
+        }
+    }
+
+    /// Give up each query after `timeout`.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Answers for `state`, which has to be a state of this level.
+    pub fn query(&mut self, state: &SolveState) -> Result<Answer, StateError> {
+        let map = self.map.with_checked_solve_state(state)?;
+        Ok(self.query_map(&map))
+    }
+
+    /// Answers for the state of `map`, which has to be a map of this level.
+    pub fn query_map(&mut self, map: &Map) -> Answer {
+        self.stats = SolverStats::default();
+        let knowledge = self.solver.knowledge().expect("oracle without knowledge");
+        if let Some(solution) = knowledge.solution(map) {
+            return Answer::Solvable(solution);
+        }
+        if knowledge.is_unsolvable(map) {
+            return Answer::Unsolvable;
+        }
+
+        self.solver
+            .set_deadline(self.timeout.map(|timeout| Instant::now() + timeout));
+        let (solution, exhausted) = self.solver.solve_again(map);
+        self.stats = self.solver.stats();
+        match solution {
+            Some(solution) => Answer::Solvable(solution),
+            None if exhausted => Answer::Unsolvable,
+            None => Answer::Unknown,
+        }
+    }
+
+    /// Whether the state of `map` is solvable, `None` if the timeout was reached.
+    pub fn is_solvable(&mut self, map: &Map) -> Option<bool> {
+        match self.query_map(map) {
+            Answer::Solvable(_) => Some(true),
+            Answer::Unsolvable => Some(false),
+            Answer::Unknown => None,
+        }
+    }
+
+    /// Costs of an optimal solution from the state of `map`.
+    pub fn remaining_costs(&mut self, map: &Map) -> Option<Costs> {
+        match self.query_map(map) {
+            Answer::Solvable(solution) => Some(solution.costs()),
+            _ => None,
+        }
+    }
+
+    /// Number of states with a known answer.
+    pub fn known_states(&self) -> usize {
+        self.solver
+            .knowledge()
+            .map_or(0, |knowledge| knowledge.len())
+    }
+
+    /// Statistics of the last search. Queries answered from the knowledge do not search.
+    pub fn last_stats(&self) -> SolverStats {
+        self.stats
+    }
+}
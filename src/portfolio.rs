@@ -0,0 +1,147 @@
+use crate::solver::{Solver, SolverConfig, SolverStats, Strategy};
+use crate::{Map, Solution};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// When a portfolio run is finished.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Completion {
+    /// Return the first solution found by any configuration.
+    FirstSolution,
+    /// Wait for all configurations or the deadline and return the cheapest solution.
+    BestSolution,
+}
+
+pub struct PortfolioResult {
+    pub solution: Solution,
+    /// Name of the configuration which found `solution`.
+    pub winner: String,
+    pub duration: Duration,
+    /// Statistics of each configuration in the order they were added. Configurations which
+    /// were stopped early report the states they searched until then.
+    pub stats: Vec<(String, SolverStats)>,
+}
+
+/// Runs several solver configurations concurrently on the same map.
+pub struct Portfolio {
+    configs: Vec<(String, SolverConfig)>,
+    timeout: Option<Duration>,
+    completion: Completion,
+}
+
+impl Default for Portfolio {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Portfolio {
+    /// Creates an empty portfolio.
+    pub fn new() -> Self {
+        Self {
+            configs: vec![],
+            timeout: None,
+            completion: Completion::FirstSolution,
+        }
+    }
+
+    /// Portfolio of optimal and greedy searches, each with and without freeze deadlock detection,
+    /// a backward and a bidirectional search.
+    pub fn standard() -> Self {
+        let config = |strategy, freeze_deadlocks| SolverConfig {
+            strategy,
+            freeze_deadlocks,
+            ..SolverConfig::default()
+        };
+
+        Self::new()
+            .with_config("astar", config(Strategy::AStar, false))
+            .with_config("astar-freeze", config(Strategy::AStar, true))
+            .with_config("greedy", config(Strategy::Greedy, false))
+            .with_config("greedy-freeze", config(Strategy::Greedy, true))
+            .with_config("reverse", config(Strategy::Reverse, false))
+            .with_config("bidirectional", config(Strategy::Bidirectional, false))
+    }
+
+    pub fn with_config(mut self, name: impl Into<String>, config: SolverConfig) -> Self {
+        self.configs.push((name.into(), config));
+        self
+    }
+
+    /// Shared time limit of all configurations.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    pub fn with_completion(mut self, completion: Completion) -> Self {
+        self.completion = completion;
+        self
+    }
+
+    pub fn configs(&self) -> impl Iterator<Item = &str> {
+        self.configs.iter().map(|(name, _)| name.as_str())
+    }
+
+    pub fn solve(&self, map: &Map) -> Option<PortfolioResult> {
+        let start = Instant::now();
+        let deadline = self.timeout.map(|timeout| start + timeout);
+        let cancel = Arc::new(AtomicBool::new(false));
+        let (sender, receiver) = mpsc::channel();
+
+        thread::scope(|scope| {
+            for (i, (_, config)) in self.configs.iter().enumerate() {
+                let mut config = config.clone();
+                config.deadline = match (config.deadline, deadline) {
+                    (Some(a), Some(b)) => Some(a.min(b)),
+                    (a, b) => a.or(b),
+                };
+
+                let mut solver = Solver::with_config(config);
+                solver.set_cancel_flag(cancel.clone());
+                let sender = sender.clone();
+                let map = map.clone();
+                scope.spawn(move || {
+                    // receiver is gone when the winner is already known
+                    let _ = sender.send((i, solver.solve_with_stats(&map)));
+                });
+            }
+            drop(sender);
+
+            let mut stats = vec![SolverStats::default(); self.configs.len()];
+            let mut best: Option<(usize, Solution)> = None;
+            for (i, (solution, solver_stats)) in receiver.iter() {
+                stats[i] = solver_stats;
+                let solution = match solution {
+                    Some(solution) => solution,
+                    None => continue,
+                };
+
+                if best
+                    .as_ref()
+                    .is_none_or(|(_, b)| solution.costs() < b.costs())
+                {
+                    best = Some((i, solution));
+                }
+                if self.completion == Completion::FirstSolution {
+                    break;
+                }
+            }
+            let duration = Instant::now().duration_since(start);
+            cancel.store(true, Ordering::Relaxed);
+            // the other configurations stop soon after the cancellation
+            for (i, (_, solver_stats)) in receiver.iter() {
+                stats[i] = solver_stats;
+            }
+
+            best.map(|(i, solution)| PortfolioResult {
+                solution,
+                winner: self.configs[i].0.clone(),
+                duration,
+                stats: self.configs().map(String::from).zip(stats).collect(),
+            })
+        })
+    }
+}
@@ -0,0 +1,216 @@
+//! Pushes needed to move a lone box to each destination.
+
+use std::collections::VecDeque;
+
+use crate::floor::{BoxSet, Floor, NO_FLOOR};
+
+const UNREACHABLE: u32 = u32::MAX;
+
+/// Minimal pushes to move a lone box from each floor square to each destination, computed by
+/// pulling the box away from the destinations.
+///
+/// The simple distances only require floor behind the box for each push. The player aware
+/// distances also require the player to walk around the box to the side it pushes from,
+/// depending on the side of the box the player starts on. Both are lower bounds for a box among
+/// other boxes.
+pub struct PushDistances {
+    /// Floor indices of the destinations in ascending order.
+    destinations: Box<[u32]>,
+    squares: usize,
+    /// Indexed by destination and floor square.
+    simple: Box<[u32]>,
+    /// Indexed by destination, floor square and side of the player.
+    sides: Box<[u32]>,
+}
+
+impl PushDistances {
+    pub fn new(floor: &Floor, destinations: &BoxSet) -> Self {
+        let destinations: Box<[u32]> = destinations.iter().collect();
+        let squares = floor.len();
+        // for each square, the area of each neighbor when a box blocks the square
+        let areas = Self::areas(floor);
+
+        let mut simple = vec![UNREACHABLE; destinations.len() * squares].into_boxed_slice();
+        let mut sides = vec![UNREACHABLE; destinations.len() * squares * 4].into_boxed_slice();
+        for (i, d) in destinations.iter().enumerate() {
+            let range = i * squares..(i + 1) * squares;
+            Self::pull_simple(floor, *d, &mut simple[range.clone()]);
+            Self::pull_sides(
+                floor,
+                &areas,
+                *d,
+                &mut sides[range.start * 4..range.end * 4],
+            );
+        }
+
+        Self {
+            destinations,
+            squares,
+            simple,
+            sides,
+        }
+    }
+
+    /// Floor indices of the destinations. Destinations are referred to by their position in
+    /// this slice.
+    pub fn destinations(&self) -> &[u32] {
+        &self.destinations
+    }
+
+    /// Position of the destination on floor index `square`.
+    pub fn destination_index(&self, square: u32) -> Option<usize> {
+        self.destinations.binary_search(&square).ok()
+    }
+
+    /// Minimal pushes from floor index `square` to the destination, wherever the player is.
+    #[inline]
+    pub fn simple(&self, destination: usize, square: u32) -> Option<u32> {
+        let distance = self.simple[destination * self.squares + square as usize];
+        (distance != UNREACHABLE).then_some(distance)
+    }
+
+    /// Minimal pushes from floor index `square` to the destination with the player on the
+    /// neighbor in direction `side` of the box, or anywhere connected to it around the box.
+    #[inline]
+    pub fn with_player_side(&self, destination: usize, square: u32, side: usize) -> Option<u32> {
+        let distance = self.sides[(destination * self.squares + square as usize) * 4 + side];
+        (distance != UNREACHABLE).then_some(distance)
+    }
+
+    /// Minimal pushes from floor index `square` to the nearest destination.
+    pub fn nearest(&self, square: u32) -> Option<u32> {
+        (0..self.destinations.len())
+            .filter_map(|d| self.simple(d, square))
+            .min()
+    }
+
+    /// Areas of the four neighbors of each square `b` in the floor without `b`, numbered by the
+    /// first side in the area.
+    ///
+    /// Found with one depth first search: the subtree of a child of `b` is cut off from the
+    /// rest of the floor when no edge from the subtree leads above `b` (articulation points).
+    fn areas(floor: &Floor) -> Box<[[u8; 4]]> {
+        const UNSEEN: u32 = u32::MAX;
+        let squares = floor.len();
+        let mut order = vec![UNSEEN; squares];
+        // smallest order reachable from the subtree with one edge outside of the tree
+        let mut low = vec![0; squares];
+        // order after the subtree, the subtree of `v` are the orders `order[v]..end[v]`
+        let mut end = vec![0; squares];
+        let mut parent = vec![NO_FLOOR; squares];
+        let mut time = 0;
+        let mut stack: Vec<(u32, usize)> = vec![];
+        for root in 0..squares as u32 {
+            if order[root as usize] != UNSEEN {
+                continue;
+            }
+            order[root as usize] = time;
+            low[root as usize] = time;
+            time += 1;
+            stack.push((root, 0));
+            while let Some((v, side)) = stack.last_mut() {
+                let v = *v;
+                if *side == 4 {
+                    stack.pop();
+                    end[v as usize] = time;
+                    let p = parent[v as usize];
+                    if p != NO_FLOOR {
+                        low[p as usize] = low[p as usize].min(low[v as usize]);
+                    }
+                    continue;
+                }
+                let next = floor.neighbors(v)[*side];
+                *side += 1;
+                if next == NO_FLOOR {
+                    continue;
+                }
+                if order[next as usize] == UNSEEN {
+                    parent[next as usize] = v;
+                    order[next as usize] = time;
+                    low[next as usize] = time;
+                    time += 1;
+                    stack.push((next, 0));
+                } else if next != parent[v as usize] {
+                    low[v as usize] = low[v as usize].min(order[next as usize]);
+                }
+            }
+        }
+
+        (0..squares as u32)
+            .map(|b| {
+                let neighbors = floor.neighbors(b);
+                // a cut off subtree is identified by its child, the rest of the floor by `b`
+                let part = |n: u32| {
+                    let within = |v: u32| {
+                        order[v as usize] <= order[n as usize]
+                            && order[n as usize] < end[v as usize]
+                    };
+                    if !within(b) {
+                        return b;
+                    }
+                    neighbors
+                        .into_iter()
+                        .find(|c| *c != NO_FLOOR && parent[*c as usize] == b && within(*c))
+                        .filter(|c| low[*c as usize] >= order[b as usize])
+                        .unwrap_or(b)
+                };
+                let parts = neighbors.map(|n| if n == NO_FLOOR { NO_FLOOR } else { part(n) });
+                let mut areas = [u8::MAX; 4];
+                for side in 0..4 {
+                    if neighbors[side] != NO_FLOOR {
+                        areas[side] = parts.iter().position(|p| *p == parts[side]).unwrap() as u8;
+                    }
+                }
+                areas
+            })
+            .collect()
+    }
+
+    fn pull_simple(floor: &Floor, destination: u32, distances: &mut [u32]) {
+        distances[destination as usize] = 0;
+        let mut queue = VecDeque::from([destination]);
+        while let Some(b) = queue.pop_front() {
+            let distance = distances[b as usize];
+            for side in 0..4 {
+                // the player stands on `to` and steps back to `behind`
+                let to = floor.neighbors(b)[side];
+                if to == NO_FLOOR || distances[to as usize] != UNREACHABLE {
+                    continue;
+                }
+                if floor.neighbors(to)[side] != NO_FLOOR {
+                    distances[to as usize] = distance + 1;
+                    queue.push_back(to);
+                }
+            }
+        }
+    }
+
+    fn pull_sides(floor: &Floor, areas: &[[u8; 4]], destination: u32, distances: &mut [u32]) {
+        // the player walks around the box to all sides of the same area
+        let mut reach = |b: u32, side: usize, distance: u32, queue: &mut VecDeque<_>| {
+            let area = areas[b as usize][side];
+            for other in 0..4 {
+                let entry = &mut distances[b as usize * 4 + other];
+                if areas[b as usize][other] == area && *entry == UNREACHABLE {
+                    *entry = distance;
+                    queue.push_back((b, other, distance));
+                }
+            }
+        };
+
+        let mut queue = VecDeque::new();
+        // the player may end on any side of the box
+        for side in 0..4 {
+            if floor.neighbors(destination)[side] != NO_FLOOR {
+                reach(destination, side, 0, &mut queue);
+            }
+        }
+        while let Some((b, side, distance)) = queue.pop_front() {
+            let to = floor.neighbors(b)[side];
+            let behind = floor.neighbors(to)[side];
+            if behind != NO_FLOOR {
+                reach(to, side, distance + 1, &mut queue);
+            }
+        }
+    }
+}
@@ -0,0 +1,293 @@
+//! Debug views of a level, with overlays that show what the solver sees.
+
+use std::io;
+use std::thread;
+use std::time::Duration;
+
+use crate::bitboard::BitBoard;
+use crate::{Costs, Direction, Map, Move, Pos};
+
+/// Renders a map as text with configurable overlays. Boxes are drawn as `o` (`O` on a
+/// destination) so that they can not be confused with standard XSB output.
+///
+/// Overlays only change squares without walls, boxes and the player, in this precedence:
+///
+/// * push-distance heatmap: pushes to move a box from the square to the selected destination
+///   as `0`-`9` and `A`-`Z`, `^` for larger distances, `!` if it can not be reached
+/// * corrals: floor the player can not reach, labeled `a`-`z` per region
+/// * tunnels: `=` for squares between two walls
+/// * dead squares: `~`
+/// * player-reachable region: `:`
+///
+/// Frozen boxes which form a deadlock are drawn as `X`.
+#[derive(Clone, Debug, Default)]
+pub struct DebugRenderer {
+    dead_squares: bool,
+    reachable: bool,
+    frozen_boxes: bool,
+    corrals: bool,
+    tunnels: bool,
+    heatmap: Option<Pos>,
+}
+
+impl DebugRenderer {
+    /// Renderer without overlays.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Renderer with all overlays except the heatmap.
+    pub fn all() -> Self {
+        Self {
+            dead_squares: true,
+            reachable: true,
+            frozen_boxes: true,
+            corrals: true,
+            tunnels: true,
+            heatmap: None,
+        }
+    }
+
+    pub fn with_dead_squares(mut self, enabled: bool) -> Self {
+        self.dead_squares = enabled;
+        self
+    }
+
+    pub fn with_reachable(mut self, enabled: bool) -> Self {
+        self.reachable = enabled;
+        self
+    }
+
+    pub fn with_frozen_boxes(mut self, enabled: bool) -> Self {
+        self.frozen_boxes = enabled;
+        self
+    }
+
+    pub fn with_corrals(mut self, enabled: bool) -> Self {
+        self.corrals = enabled;
+        self
+    }
+
+    pub fn with_tunnels(mut self, enabled: bool) -> Self {
+        self.tunnels = enabled;
+        self
+    }
+
+    /// Shows the push distances of a box to `goal`, if it is a destination.
+    pub fn with_heatmap(mut self, goal: Option<Pos>) -> Self {
+        self.heatmap = goal;
+        self
+    }
+
+    pub fn render(&self, map: &Map) -> String {
+        let reachable = map.reachable();
+        let corrals = if self.corrals {
+            Self::corrals(map, &reachable)
+        } else {
+            vec![]
+        };
+        let heatmap = self.heatmap.filter(|goal| map.is_destination(*goal));
+
+        let mut result = String::new();
+        for y in 0..map.height() {
+            for x in 0..map.width() {
+                let pos = Pos::new(x as u16, y as u16);
+                let square = map.square(pos).unwrap();
+                let destination = map.is_destination(pos);
+                let c = if map.is_wall(pos) {
+                    '#'
+                } else if map.player() == pos {
+                    if destination {
+                        '+'
+                    } else {
+                        '@'
+                    }
+                } else if map.is_box(pos) {
+                    if self.frozen_boxes && map.is_freeze_deadlock(pos) {
+                        'X'
+                    } else if destination {
+                        'O'
+                    } else {
+                        'o'
+                    }
+                } else if destination {
+                    '.'
+                } else if let Some(goal) = heatmap {
+                    Self::heat(map.push_distance(pos, goal))
+                } else if let Some(&Some(corral)) = corrals.get(square) {
+                    (b'a' + (corral % 26) as u8) as char
+                } else if self.tunnels && Self::is_tunnel(map, square) {
+                    '='
+                } else if self.dead_squares && map.is_dead(pos) {
+                    '~'
+                } else if self.reachable && reachable.contains(square) {
+                    ':'
+                } else {
+                    ' '
+                };
+                result.push(c);
+            }
+            result.push('\n');
+        }
+        result
+    }
+
+    fn heat(distance: Option<u32>) -> char {
+        match distance {
+            None => '!',
+            Some(d @ 0..=9) => (b'0' + d as u8) as char,
+            Some(d @ 10..=35) => (b'A' + (d - 10) as u8) as char,
+            Some(_) => '^',
+        }
+    }
+
+    fn is_tunnel(map: &Map, square: usize) -> bool {
+        let [up, down, left, right] = map.offsets();
+        let wall = |offset: isize| map.is_wall_square(square.wrapping_add_signed(offset));
+        (wall(up) && wall(down)) || (wall(left) && wall(right))
+    }
+
+    /// Corral label of each floor square the player can not reach.
+    fn corrals(map: &Map, reachable: &BitBoard) -> Vec<Option<usize>> {
+        let mut labels = vec![None; map.squares()];
+        let mut count = 0;
+        let mut stack = vec![];
+        for floor in 0..map.floor().len() as u32 {
+            let start = map.floor().square(floor);
+            if labels[start].is_some() || reachable.contains(start) || !map.is_free_square(start) {
+                continue;
+            }
+            labels[start] = Some(count);
+            stack.push(start);
+            while let Some(square) = stack.pop() {
+                for offset in map.offsets() {
+                    let next = square.wrapping_add_signed(offset);
+                    if labels[next].is_none() && map.is_free_square(next) {
+                        labels[next] = Some(count);
+                        stack.push(next);
+                    }
+                }
+            }
+            count += 1;
+        }
+        labels
+    }
+}
+
+impl Map {
+    /// Debug view with dead squares, see `DebugRenderer`.
+    pub fn debug_view(&self) -> String {
+        DebugRenderer::new().with_dead_squares(true).render(self)
+    }
+}
+
+const RESET: &str = "\x1b[0m";
+const WALL: &str = "\x1b[34m";
+const GOAL: &str = "\x1b[31m";
+const BOX: &str = "\x1b[33m";
+const BOX_ON_GOAL: &str = "\x1b[1;32m";
+const PLAYER: &str = "\x1b[1;36m";
+const CLEAR_SCREEN: &str = "\x1b[2J\x1b[H";
+
+/// Renders a map in XSB characters with ANSI colors for terminals.
+#[derive(Clone, Debug)]
+pub struct AnsiRenderer {
+    colors: bool,
+    delay: Duration,
+}
+
+impl Default for AnsiRenderer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AnsiRenderer {
+    pub fn new() -> Self {
+        Self {
+            colors: true,
+            delay: Duration::from_millis(100),
+        }
+    }
+
+    /// Plain XSB output without escape sequences.
+    pub fn with_colors(mut self, colors: bool) -> Self {
+        self.colors = colors;
+        self
+    }
+
+    /// Time between two steps of a replay.
+    pub fn with_delay(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+
+    pub fn render(&self, map: &Map) -> String {
+        let xsb = map.to_xsb();
+        if !self.colors {
+            return xsb + "\n";
+        }
+
+        let mut result = String::new();
+        for line in xsb.lines() {
+            for c in line.chars() {
+                let color = match c {
+                    '#' => WALL,
+                    '.' => GOAL,
+                    '$' => BOX,
+                    '*' => BOX_ON_GOAL,
+                    '@' | '+' => PLAYER,
+                    _ => {
+                        result.push(c);
+                        continue;
+                    }
+                };
+                result.push_str(color);
+                result.push(c);
+                result.push_str(RESET);
+            }
+            result.push('\n');
+        }
+        result
+    }
+
+    /// Animates the player path `lurd` step by step, redrawing the screen for every step.
+    pub fn replay(&self, map: &Map, lurd: &str, out: &mut impl io::Write) -> io::Result<()> {
+        let invalid = |c: char| io::Error::new(io::ErrorKind::InvalidInput, format!("step {}", c));
+
+        let mut map = map.clone();
+        let mut costs = Costs::zero();
+        self.frame(&map, costs, out)?;
+        for c in lurd.chars() {
+            let (direction, push) = Direction::from_lurd(c).ok_or_else(|| invalid(c))?;
+            let next = map.player().step(direction).ok_or_else(|| invalid(c))?;
+            if push {
+                let m = Move::in_direction(next, direction).ok_or_else(|| invalid(c))?;
+                if !map.is_box(next) || !map.is_free(m.end) {
+                    return Err(invalid(c));
+                }
+                map.apply_move(m);
+                costs.pushes += 1;
+            } else {
+                if !map.is_free(next) {
+                    return Err(invalid(c));
+                }
+                map.set_player_pos(next);
+            }
+            costs.moves += 1;
+
+            thread::sleep(self.delay);
+            self.frame(&map, costs, out)?;
+        }
+        Ok(())
+    }
+
+    fn frame(&self, map: &Map, costs: Costs, out: &mut impl io::Write) -> io::Result<()> {
+        if self.colors {
+            out.write_all(CLEAR_SCREEN.as_bytes())?;
+        }
+        write!(out, "{}", self.render(map))?;
+        writeln!(out, "moves: {}, pushes: {}", costs.moves, costs.pushes)?;
+        out.flush()
+    }
+}
@@ -0,0 +1,383 @@
+//! Searches which pull boxes backward from the solved level.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use likely_stable::unlikely;
+
+use crate::algos::bfs::DistanceMap;
+use crate::floor::{BoxSet, NO_FLOOR};
+use crate::solver::SolverStats;
+use crate::{Costs, Map, Push, Solution, SolveState};
+
+/// Marks the roots in the node arenas.
+const ROOT: u32 = u32::MAX;
+
+/// Parent node and the push to the node. For nodes found by pulls, the push undoes the pull.
+type Node = (u32, Push);
+
+/// Move generation over states which are identified by the boxes and the region of the player.
+/// The player of a state is the smallest floor index of its region.
+struct Moves<'a> {
+    map: &'a Map,
+    /// Floor squares from which a lone box can be pulled back to one of its start squares.
+    live: BoxSet,
+}
+
+impl<'a> Moves<'a> {
+    fn new(map: &'a Map) -> Self {
+        Self {
+            map,
+            live: Self::live_squares(map),
+        }
+    }
+
+    /// Normalized solved states, one for each region the player may end in. Empty if a
+    /// destination can never be reached from the start.
+    fn solved(&self) -> Vec<(SolveState, BoxSet)> {
+        let floor = self.map.floor();
+        let destinations = &self.map.props.destinations;
+        if destinations.iter().any(|d| !self.live.contains(d)) {
+            return vec![];
+        }
+        let mut seen = HashSet::new();
+        let mut solved = vec![];
+        for player in 0..floor.len() as u32 {
+            if destinations.contains(player) {
+                continue;
+            }
+            let (state, region) =
+                self.normalized(SolveState::new(player, destinations.clone(), floor));
+            if seen.insert(state.clone()) {
+                solved.push((state, region));
+            }
+        }
+        solved
+    }
+
+    /// Pulls of boxes the player can reach, as the pushes which undo them and the states
+    /// before those pushes.
+    fn pulls(&self, state: &SolveState, region: &BoxSet) -> Vec<(Push, SolveState, BoxSet)> {
+        let floor = self.map.floor();
+        let mut pulls = vec![];
+        for square in region.iter() {
+            if !self.live.contains(square) {
+                continue;
+            }
+            let neighbors = floor.neighbors(square);
+            for (direction, &b) in neighbors.iter().enumerate() {
+                if b == NO_FLOOR || !state.boxes.contains(b) {
+                    continue;
+                }
+                // the player steps back from the box and pulls it onto its own square
+                let behind = neighbors[direction ^ 1];
+                if behind == NO_FLOOR || state.boxes.contains(behind) {
+                    continue;
+                }
+                let mut next = state.clone();
+                next.apply_push(floor, b, square);
+                next.player = behind;
+                let (next, region) = self.normalized(next);
+                let push = Push {
+                    from: floor.square(square) as u32,
+                    to: floor.square(b) as u32,
+                };
+                pulls.push((push, next, region));
+            }
+        }
+        pulls
+    }
+
+    /// Pushes of boxes the player can reach, except onto dead squares.
+    fn pushes(&self, state: &SolveState, region: &BoxSet) -> Vec<(Push, SolveState, BoxSet)> {
+        let floor = self.map.floor();
+        let mut pushes = vec![];
+        for square in region.iter() {
+            let neighbors = floor.neighbors(square);
+            for (direction, &b) in neighbors.iter().enumerate() {
+                if b == NO_FLOOR || !state.boxes.contains(b) {
+                    continue;
+                }
+                let to = floor.neighbors(b)[direction];
+                if to == NO_FLOOR
+                    || state.boxes.contains(to)
+                    || self.map.is_dead_square(floor.square(to))
+                {
+                    continue;
+                }
+                let mut next = state.clone();
+                next.apply_push(floor, b, to);
+                let (next, region) = self.normalized(next);
+                let push = Push {
+                    from: floor.square(b) as u32,
+                    to: floor.square(to) as u32,
+                };
+                pushes.push((push, next, region));
+            }
+        }
+        pushes
+    }
+
+    /// Solution of the pushes from the start of the map, with the shortest walks between them.
+    fn solution(&self, pushes: impl Iterator<Item = Push>) -> Solution {
+        let mut map = self.map.clone();
+        let mut distances = DistanceMap::new();
+        let mut moves = vec![];
+        let mut costs = Costs::zero();
+        for push in pushes {
+            distances.update(&map);
+            let walk = distances.distance(push.player_square()).unwrap_or_default();
+            costs.moves += walk + 1;
+            costs.pushes += 1;
+            moves.push(map.to_move(push));
+            map.apply_push(push);
+        }
+        Solution::new(moves, costs)
+    }
+
+    /// Floor squares the player can walk to.
+    fn region(&self, state: &SolveState) -> BoxSet {
+        let floor = self.map.floor();
+        let mut region = BoxSet::new(floor.len());
+        region.insert(state.player);
+        let mut stack = vec![state.player];
+        while let Some(square) = stack.pop() {
+            for next in floor.neighbors(square) {
+                if next != NO_FLOOR && !state.boxes.contains(next) && !region.contains(next) {
+                    region.insert(next);
+                    stack.push(next);
+                }
+            }
+        }
+        region
+    }
+
+    /// Moves the player to the smallest floor index of its region. Returns the region as well,
+    /// so that it is only computed once per state.
+    fn normalized(&self, mut state: SolveState) -> (SolveState, BoxSet) {
+        let region = self.region(&state);
+        state.player = region.iter().next().unwrap_or(state.player);
+        (state, region)
+    }
+
+    /// Reverse dead squares: a box can only be pulled to squares which a lone box can be pushed
+    /// to from one of the start squares of the boxes.
+    fn live_squares(map: &Map) -> BoxSet {
+        let floor = map.floor();
+        let mut live = BoxSet::new(floor.len());
+        let mut stack: Vec<u32> = map.solve_state().boxes.iter().collect();
+        for b in &stack {
+            live.insert(*b);
+        }
+        while let Some(square) = stack.pop() {
+            let neighbors = floor.neighbors(square);
+            for (direction, &to) in neighbors.iter().enumerate() {
+                let player = neighbors[direction ^ 1];
+                if to != NO_FLOOR && player != NO_FLOOR && !live.contains(to) {
+                    live.insert(to);
+                    stack.push(to);
+                }
+            }
+        }
+        live
+    }
+}
+
+/// Pushes of the path from `node` to a root, nearest to `node` first.
+fn path(nodes: &[Node], mut node: u32) -> impl Iterator<Item = Push> + '_ {
+    std::iter::from_fn(move || {
+        if node == ROOT {
+            return None;
+        }
+        let (parent, push) = nodes[node as usize];
+        node = parent;
+        Some(push)
+    })
+}
+
+/// Breadth first search over pulls, starting from the boxes on the destinations with the
+/// player in each region. The pulls found reversed are a push optimal solution, the moves are
+/// not minimized.
+pub(crate) struct ReverseSearch<'a> {
+    moves: Moves<'a>,
+    nodes: Vec<Node>,
+    stats: SolverStats,
+}
+
+impl<'a> ReverseSearch<'a> {
+    pub fn new(map: &'a Map) -> Self {
+        Self {
+            moves: Moves::new(map),
+            nodes: vec![],
+            stats: SolverStats::default(),
+        }
+    }
+
+    pub fn stats(&self) -> SolverStats {
+        self.stats
+    }
+
+    /// Searches until the start state of the map is reached or `interrupted` returns true for
+    /// the number of expanded states.
+    pub fn solve(&mut self, interrupted: impl Fn(usize) -> bool) -> Option<Solution> {
+        let start = self.moves.map.solve_state();
+        let mut tried = HashSet::new();
+        let mut queue = VecDeque::new();
+        for (state, region) in self.moves.solved() {
+            tried.insert(state.clone());
+            queue.push_back((ROOT, state, region));
+        }
+
+        let mut solution = None;
+        while let Some((node, state, region)) = queue.pop_front() {
+            if unlikely(interrupted(self.stats.expanded)) {
+                queue.push_front((node, state, region));
+                break;
+            }
+            self.stats.expanded += 1;
+
+            if state.boxes == start.boxes && region.contains(start.player) {
+                // the pulls are found from the solved state backwards, so the pushes which
+                // undo them are already in forward order
+                solution = Some(self.moves.solution(path(&self.nodes, node)));
+                break;
+            }
+
+            for (push, next, next_region) in self.moves.pulls(&state, &region) {
+                if !tried.insert(next.clone()) {
+                    continue;
+                }
+                self.stats.generated += 1;
+                self.nodes.push((node, push));
+                let node = (self.nodes.len() - 1) as u32;
+                queue.push_back((node, next, next_region));
+            }
+        }
+
+        self.stats.open = queue.len();
+        let state_size = size_of::<SolveState>() + start.heap_size();
+        self.stats.memory = self.nodes.capacity() * size_of::<Node>()
+            + queue.capacity() * size_of::<(u32, SolveState, BoxSet, usize)>()
+            + queue.len() * 2 * start.heap_size()
+            + tried.capacity() * (state_size + 1);
+        solution
+    }
+}
+
+/// Breadth first searches forward from the start and backward from the solved states, which
+/// alternately expand a whole layer of the smaller frontier until they meet. Finds push optimal
+/// solutions, the moves are not minimized.
+pub(crate) struct BidirectionalSearch<'a> {
+    moves: Moves<'a>,
+    stats: SolverStats,
+}
+
+/// One direction of `BidirectionalSearch`.
+#[derive(Default)]
+struct Side {
+    nodes: Vec<Node>,
+    /// Node and depth of every state found.
+    seen: HashMap<SolveState, (u32, u32)>,
+    frontier: Vec<(u32, SolveState, BoxSet)>,
+    depth: u32,
+}
+
+impl Side {
+    fn add(&mut self, node: u32, state: SolveState, region: BoxSet) {
+        self.seen.insert(state.clone(), (node, self.depth));
+        self.frontier.push((node, state, region));
+    }
+}
+
+impl<'a> BidirectionalSearch<'a> {
+    pub fn new(map: &'a Map) -> Self {
+        Self {
+            moves: Moves::new(map),
+            stats: SolverStats::default(),
+        }
+    }
+
+    pub fn stats(&self) -> SolverStats {
+        self.stats
+    }
+
+    /// Searches until both directions meet or `interrupted` returns true for the number of
+    /// expanded states.
+    pub fn solve(&mut self, interrupted: impl Fn(usize) -> bool) -> Option<Solution> {
+        let mut forward = Side::default();
+        let mut backward = Side::default();
+        let (start, region) = self.moves.normalized(self.moves.map.solve_state().clone());
+        forward.add(ROOT, start.clone(), region);
+        for (state, region) in self.moves.solved() {
+            backward.add(ROOT, state, region);
+        }
+        if let Some((node, _)) = backward.seen.get(&start) {
+            return Some(self.moves.solution(path(&backward.nodes, *node)));
+        }
+
+        let mut meeting = None;
+        'search: while meeting.is_none()
+            && !forward.frontier.is_empty()
+            && !backward.frontier.is_empty()
+        {
+            let pulling = backward.frontier.len() < forward.frontier.len();
+            let (side, other) = if pulling {
+                (&mut backward, &forward)
+            } else {
+                (&mut forward, &backward)
+            };
+            side.depth += 1;
+            let mut best: Option<(u32, u32, u32)> = None;
+            for (node, state, region) in std::mem::take(&mut side.frontier) {
+                if unlikely(interrupted(self.stats.expanded)) {
+                    break 'search;
+                }
+                self.stats.expanded += 1;
+                let successors = if pulling {
+                    self.moves.pulls(&state, &region)
+                } else {
+                    self.moves.pushes(&state, &region)
+                };
+                for (push, next, next_region) in successors {
+                    if side.seen.contains_key(&next) {
+                        continue;
+                    }
+                    self.stats.generated += 1;
+                    side.nodes.push((node, push));
+                    let next_node = (side.nodes.len() - 1) as u32;
+                    // the whole layer is expanded, a later meeting may be cheaper
+                    if let Some((other_node, other_depth)) = other.seen.get(&next) {
+                        let total = side.depth + other_depth;
+                        if best.is_none_or(|(_, _, best)| total < best) {
+                            best = Some((next_node, *other_node, total));
+                        }
+                    }
+                    side.add(next_node, next, next_region);
+                }
+            }
+            if let Some((node, other_node, _)) = best {
+                meeting = Some(if pulling {
+                    (other_node, node)
+                } else {
+                    (node, other_node)
+                });
+            }
+        }
+
+        self.stats.open = forward.frontier.len() + backward.frontier.len();
+        self.stats.memory = [&forward, &backward]
+            .iter()
+            .map(|side| {
+                side.nodes.capacity() * size_of::<Node>()
+                    + side.seen.capacity() * (size_of::<(SolveState, (u32, u32))>() + 1)
+                    + side.seen.len() * start.heap_size()
+                    + side.frontier.capacity() * size_of::<(u32, SolveState, BoxSet)>()
+            })
+            .sum();
+
+        let (forward_node, backward_node) = meeting?;
+        let mut pushes: Vec<Push> = path(&forward.nodes, forward_node).collect();
+        pushes.reverse();
+        pushes.extend(path(&backward.nodes, backward_node));
+        Some(self.moves.solution(pushes.into_iter()))
+    }
+}
@@ -0,0 +1,164 @@
+//! Exact solutions of small levels by enumerating all solvable states.
+
+use std::cmp::Reverse;
+use std::collections::hash_map::Entry;
+use std::collections::{BinaryHeap, HashMap};
+
+use crate::algos::bfs::DistanceMap;
+use crate::floor::NO_FLOOR;
+use crate::{Costs, Direction, Map, Move, Solution, SolveState};
+
+/// All states of a level from which it can be solved, with the costs of an optimal solution
+/// from each of them.
+///
+/// The states are enumerated backwards from all solved states, by walking and pulling boxes,
+/// in order of increasing costs. States of the level which are not in the table can not be
+/// solved. Only usable for small levels, the number of states grows exponentially with the
+/// number of boxes.
+pub struct StateSpace {
+    map: Map,
+    states: HashMap<SolveState, Costs>,
+}
+
+impl StateSpace {
+    /// Enumerates the solvable states of the level of `map`. Returns `None` if there are more
+    /// than `max_states`.
+    pub fn enumerate(map: &Map, max_states: usize) -> Option<Self> {
+        let floor = map.floor();
+        let destinations = &map.props.destinations;
+
+        let mut states: HashMap<SolveState, Costs> = HashMap::new();
+        let mut queue = BinaryHeap::new();
+        // the player may stand anywhere when the level is solved
+        for player in 0..floor.len() as u32 {
+            if !destinations.contains(player) {
+                let state = SolveState::new(player, destinations.clone(), floor);
+                states.insert(state.clone(), Costs::zero());
+                queue.push(Reverse((Costs::zero(), state)));
+            }
+        }
+
+        while let Some(Reverse((costs, state))) = queue.pop() {
+            if states.get(&state).is_some_and(|best| *best < costs) {
+                continue;
+            }
+
+            let mut predecessors = vec![];
+            let neighbors = floor.neighbors(state.player);
+            for (direction, &next) in neighbors.iter().enumerate() {
+                if next == NO_FLOOR || state.boxes.contains(next) {
+                    continue;
+                }
+                // the player walked from `next`
+                let mut walked = state.clone();
+                walked.player = next;
+                predecessors.push((walked, Costs::new(costs.pushes, costs.moves + 1)));
+
+                // the player pushed the box on the opposite side from its own square
+                let opposite = neighbors[direction ^ 1];
+                if opposite != NO_FLOOR && state.boxes.contains(opposite) {
+                    let mut pulled = state.clone();
+                    pulled.apply_push(floor, opposite, state.player);
+                    pulled.player = next;
+                    predecessors.push((pulled, Costs::new(costs.pushes + 1, costs.moves + 1)));
+                }
+            }
+
+            for (predecessor, costs) in predecessors {
+                match states.entry(predecessor) {
+                    Entry::Occupied(mut entry) => {
+                        if costs < *entry.get() {
+                            entry.insert(costs);
+                            queue.push(Reverse((costs, entry.key().clone())));
+                        }
+                    }
+                    Entry::Vacant(entry) => {
+                        queue.push(Reverse((costs, entry.key().clone())));
+                        entry.insert(costs);
+                    }
+                }
+            }
+            if states.len() > max_states {
+                return None;
+            }
+        }
+
+        Some(Self {
+            map: map.clone(),
+            states,
+        })
+    }
+
+    /// Number of solvable states.
+    pub fn len(&self) -> usize {
+        self.states.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.states.is_empty()
+    }
+
+    /// Solvable states with the costs of an optimal solution.
+    pub fn iter(&self) -> impl Iterator<Item = (&SolveState, Costs)> + '_ {
+        self.states.iter().map(|(state, costs)| (state, *costs))
+    }
+
+    /// Costs of an optimal solution from `state`, `None` if it can not be solved.
+    pub fn remaining(&self, state: &SolveState) -> Option<Costs> {
+        self.states.get(state).copied()
+    }
+
+    pub fn is_solvable(&self, map: &Map) -> bool {
+        self.states.contains_key(map.solve_state())
+    }
+
+    /// First push of an optimal solution from the state of `map`.
+    pub fn best_move(&self, map: &Map) -> Option<Move> {
+        if map.is_solved() {
+            return None;
+        }
+        let remaining = self.remaining(map.solve_state())?;
+        let floor = map.floor();
+        let distances = DistanceMap::from_player(map);
+        let state = map.solve_state();
+        // all pushes, the dead squares of the map are not trusted here
+        for b in state.boxes.iter() {
+            let neighbors = floor.neighbors(b);
+            for (direction, &to) in neighbors.iter().enumerate() {
+                let player = neighbors[direction ^ 1];
+                if to == NO_FLOOR || player == NO_FLOOR || state.boxes.contains(to) {
+                    continue;
+                }
+                let walk = match distances.distance(floor.square(player)) {
+                    Some(walk) => walk,
+                    None => continue,
+                };
+                let mut next = state.clone();
+                next.apply_push(floor, b, to);
+                let found = self.remaining(&next).is_some_and(|rest| {
+                    Costs::new(rest.pushes + 1, rest.moves + walk + 1) == remaining
+                });
+                if found {
+                    return Some(Move {
+                        start: map.pos(floor.square(b)),
+                        end: map.pos(floor.square(to)),
+                        direction: Direction::ALL[direction],
+                    });
+                }
+            }
+        }
+        None
+    }
+
+    /// Optimal solution from the state of `map`.
+    pub fn solution(&self, map: &Map) -> Option<Solution> {
+        let costs = self.remaining(map.solve_state())?;
+        let mut map = self.map.with_solve_state(map.solve_state().clone());
+        let mut moves = vec![];
+        while let Some(m) = self.best_move(&map) {
+            map.apply_move(m);
+            moves.push(m);
+        }
+        Some(Solution::new(moves, costs))
+    }
+}
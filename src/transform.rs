@@ -0,0 +1,312 @@
+use crate::bitboard::BitBoard;
+use crate::{CellState, Direction, Input, Map, Move, Pos, Solution};
+
+/// Rotations and reflections of a board, the dihedral group of the square.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash)]
+pub enum Transform {
+    Identity,
+    /// Clockwise rotation by 90 degrees.
+    Rotate90,
+    Rotate180,
+    /// Clockwise rotation by 270 degrees.
+    Rotate270,
+    /// Mirror at the vertical axis, left and right are swapped.
+    FlipHorizontal,
+    /// Mirror at the horizontal axis, up and down are swapped.
+    FlipVertical,
+    /// Mirror at the main diagonal, x and y are swapped.
+    Transpose,
+    /// Mirror at the anti diagonal.
+    AntiTranspose,
+}
+
+impl Transform {
+    pub const ALL: [Transform; 8] = [
+        Transform::Identity,
+        Transform::Rotate90,
+        Transform::Rotate180,
+        Transform::Rotate270,
+        Transform::FlipHorizontal,
+        Transform::FlipVertical,
+        Transform::Transpose,
+        Transform::AntiTranspose,
+    ];
+
+    /// Whether width and height of the board are swapped.
+    pub fn swaps_axes(self) -> bool {
+        matches!(
+            self,
+            Transform::Rotate90
+                | Transform::Rotate270
+                | Transform::Transpose
+                | Transform::AntiTranspose
+        )
+    }
+
+    /// Size of a `width` x `height` board after the transform.
+    pub fn size(self, width: usize, height: usize) -> (usize, usize) {
+        if self.swaps_axes() {
+            (height, width)
+        } else {
+            (width, height)
+        }
+    }
+
+    /// Position of `pos` of a `width` x `height` board after the transform.
+    pub fn apply(self, pos: Pos, width: usize, height: usize) -> Pos {
+        let [x, y] = self.apply_coordinates([pos.x as i64, pos.y as i64], width, height);
+        Pos::new(x as u16, y as u16)
+    }
+
+    /// Like `apply`, but for coordinates outside of the board. On a 1 x 1 board this is the
+    /// linear part of the transform.
+    fn apply_coordinates(self, [x, y]: [i64; 2], width: usize, height: usize) -> [i64; 2] {
+        let (w, h) = (width as i64 - 1, height as i64 - 1);
+        match self {
+            Transform::Identity => [x, y],
+            Transform::Rotate90 => [h - y, x],
+            Transform::Rotate180 => [w - x, h - y],
+            Transform::Rotate270 => [y, w - x],
+            Transform::FlipHorizontal => [w - x, y],
+            Transform::FlipVertical => [x, h - y],
+            Transform::Transpose => [y, x],
+            Transform::AntiTranspose => [h - y, w - x],
+        }
+    }
+
+    /// Direction after the transform.
+    pub fn apply_direction(self, direction: Direction) -> Direction {
+        use Direction::*;
+
+        let index = direction.index();
+        let mapped = match self {
+            Transform::Identity => [Up, Down, Left, Right],
+            Transform::Rotate90 => [Right, Left, Up, Down],
+            Transform::Rotate180 => [Down, Up, Right, Left],
+            Transform::Rotate270 => [Left, Right, Down, Up],
+            Transform::FlipHorizontal => [Up, Down, Right, Left],
+            Transform::FlipVertical => [Down, Up, Left, Right],
+            Transform::Transpose => [Left, Right, Up, Down],
+            Transform::AntiTranspose => [Right, Left, Down, Up],
+        };
+        mapped[index]
+    }
+
+    /// Transform which undoes this one.
+    pub fn inverse(self) -> Self {
+        match self {
+            Transform::Rotate90 => Transform::Rotate270,
+            Transform::Rotate270 => Transform::Rotate90,
+            other => other,
+        }
+    }
+
+    /// Transform which applies `self` first and `next` afterwards.
+    fn then(self, next: Transform) -> Self {
+        // the directions determine the rotation or reflection
+        Transform::ALL
+            .into_iter()
+            .find(|t| {
+                Direction::ALL.iter().all(|d| {
+                    t.apply_direction(*d) == next.apply_direction(self.apply_direction(*d))
+                })
+            })
+            .unwrap()
+    }
+}
+
+/// Mapping of positions of one board to positions of a rotated, mirrored or cropped board.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct BoardMapping {
+    /// Applied to the positions as if the board had only one square.
+    transform: Transform,
+    /// Added to the positions after the transform.
+    offset: [i64; 2],
+}
+
+impl BoardMapping {
+    pub fn identity() -> Self {
+        Self::crop(0, 0)
+    }
+
+    /// Mapping of `transform` applied to a `width` x `height` board.
+    pub fn transform(transform: Transform, width: usize, height: usize) -> Self {
+        let offset = transform.apply_coordinates([0, 0], width.max(1), height.max(1));
+        Self { transform, offset }
+    }
+
+    /// Mapping which removes `columns` columns on the left and `rows` rows on the top.
+    pub fn crop(columns: usize, rows: usize) -> Self {
+        Self {
+            transform: Transform::Identity,
+            offset: [-(columns as i64), -(rows as i64)],
+        }
+    }
+
+    /// Mapping which applies `self` first and `next` afterwards.
+    pub fn then(self, next: BoardMapping) -> Self {
+        let [x, y] = next.linear(self.offset);
+        Self {
+            transform: self.transform.then(next.transform),
+            offset: [x + next.offset[0], y + next.offset[1]],
+        }
+    }
+
+    /// Mapping back to the original board.
+    pub fn inverse(self) -> Self {
+        let transform = self.transform.inverse();
+        let [x, y] = transform.apply_coordinates(self.offset, 1, 1);
+        Self {
+            transform,
+            offset: [-x, -y],
+        }
+    }
+
+    /// Mapped position, if it is not cropped away.
+    pub fn map_pos(&self, pos: Pos) -> Option<Pos> {
+        let [x, y] = self.linear([pos.x as i64, pos.y as i64]);
+        let (x, y) = (x + self.offset[0], y + self.offset[1]);
+        Some(Pos::new(u16::try_from(x).ok()?, u16::try_from(y).ok()?))
+    }
+
+    pub fn map_direction(&self, direction: Direction) -> Direction {
+        self.transform.apply_direction(direction)
+    }
+
+    pub fn map_move(&self, m: Move) -> Option<Move> {
+        Move::in_direction(self.map_pos(m.start)?, self.map_direction(m.direction))
+    }
+
+    /// Solution with all pushes mapped. The costs do not change.
+    pub fn map_solution(&self, solution: &Solution) -> Option<Solution> {
+        let moves = solution
+            .moves()
+            .iter()
+            .map(|m| self.map_move(*m))
+            .collect::<Option<Vec<_>>>()?;
+        Some(Solution::new(moves, solution.costs()))
+    }
+
+    fn linear(&self, coordinates: [i64; 2]) -> [i64; 2] {
+        self.transform.apply_coordinates(coordinates, 1, 1)
+    }
+}
+
+impl Input {
+    /// Rotated or mirrored level, padded to a rectangle.
+    pub fn transformed(&self, transform: Transform) -> (Input, BoardMapping) {
+        let (width, height) = (self.width(), self.height());
+        let mapping = BoardMapping::transform(transform, width, height);
+        let (new_width, new_height) = transform.size(width, height);
+
+        let mut input = vec![vec![CellState::Empty; new_width]; new_height];
+        for (y, line) in self.padded().input.into_iter().enumerate() {
+            for (x, cell) in line.into_iter().enumerate() {
+                let pos = mapping.map_pos(Pos::new(x as u16, y as u16)).unwrap();
+                input[pos.y as usize][pos.x as usize] = cell;
+            }
+        }
+        (Input { input }, mapping)
+    }
+
+    /// Level without empty outer rows and columns and without empty squares at the end of
+    /// lines.
+    pub fn trimmed(&self) -> (Input, BoardMapping) {
+        let is_empty = |cell: &CellState| *cell == CellState::Empty;
+        let top = self
+            .input
+            .iter()
+            .position(|line| !line.iter().all(is_empty))
+            .unwrap_or(self.input.len());
+        let bottom = self
+            .input
+            .iter()
+            .rposition(|line| !line.iter().all(is_empty))
+            .map_or(top, |y| y + 1);
+        let left = self.input[top..bottom]
+            .iter()
+            .filter_map(|line| line.iter().position(|c| !is_empty(c)))
+            .min()
+            .unwrap_or_default();
+
+        let input = self.input[top..bottom]
+            .iter()
+            .map(|line| {
+                let end = line.iter().rposition(|c| !is_empty(c)).map_or(0, |x| x + 1);
+                line[left.min(end)..end].to_vec()
+            })
+            .collect();
+        (Input { input }, BoardMapping::crop(left, top))
+    }
+
+    /// Level where all squares outside of the walls are empty, including boxes and
+    /// destinations placed there for decoration. Positions do not change.
+    pub fn without_decoration(&self) -> Input {
+        let (width, height) = (self.width(), self.height());
+        // one more column which is never free, so that steps do not wrap around lines
+        let stride = width + 1;
+        let offsets = [-(stride as isize), stride as isize, -1, 1];
+        let square = |x: usize, y: usize| y * stride + x;
+
+        let mut free = BitBoard::new(stride * height);
+        for (y, line) in self.padded().input.iter().enumerate() {
+            for (x, cell) in line.iter().enumerate() {
+                if !cell.is_wall() {
+                    free.insert(square(x, y));
+                }
+            }
+        }
+
+        // player region ignoring boxes
+        let mut player = BitBoard::new(free.len());
+        if let Ok(pos) = self.get_player_pos() {
+            player.insert(square(pos.x as usize, pos.y as usize));
+        }
+        let reachable = player.flood_fill(&free, &offsets);
+
+        // squares connected to the border of the board, but not to the player
+        let free = free.difference(&reachable);
+        let mut border = BitBoard::new(free.len());
+        for y in 0..height {
+            for x in 0..width {
+                if x == 0 || y == 0 || x == width - 1 || y == height - 1 {
+                    border.insert(square(x, y));
+                }
+            }
+        }
+        let outside = (&border & &free).flood_fill(&free, &offsets);
+
+        let input = self
+            .input
+            .iter()
+            .enumerate()
+            .map(|(y, line)| {
+                line.iter()
+                    .enumerate()
+                    .map(|(x, cell)| {
+                        if outside.contains(square(x, y)) {
+                            CellState::Empty
+                        } else {
+                            *cell
+                        }
+                    })
+                    .collect()
+            })
+            .collect();
+        Input { input }
+    }
+
+    /// Level without decoration, trimmed and padded to a rectangle.
+    pub fn normalized(&self) -> (Input, BoardMapping) {
+        let (trimmed, mapping) = self.without_decoration().trimmed();
+        (trimmed.padded(), mapping)
+    }
+}
+
+impl Map {
+    /// Rotated or mirrored map in the current state.
+    pub fn transformed(&self, transform: Transform) -> (Map, BoardMapping) {
+        let (input, mapping) = Input::from(self).transformed(transform);
+        (Map::from(input), mapping)
+    }
+}
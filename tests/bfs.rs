@@ -0,0 +1,29 @@
+use std::str::FromStr;
+use solve_sokoban::algos::bfs::DistanceMap;
+use solve_sokoban::algos::dijkstra::{shortest_path, PathGraph};
+use solve_sokoban::{Input, Map, Pos};
+
+#[test]
+fn test_distances_match_dijkstra() {
+    for level in [
+        include_str!("ttac2021/level1.txt"),
+        include_str!("ttac2021/level2.txt"),
+        include_str!("ttac2021/level3.txt"),
+    ] {
+        let map = Map::from(Input::from_str(level).unwrap());
+        let distances = DistanceMap::from_player(&map);
+        let graph = PathGraph::new(&map);
+        for y in 0..map.height() as u16 {
+            for x in 0..map.width() as u16 {
+                let pos = Pos::new(x, y);
+                let expected = if map.is_free(pos) {
+                    shortest_path(&map, &graph, map.player(), pos)
+                } else {
+                    None
+                };
+                assert_eq!(expected, distances.distance_to(&map, pos), "{}", pos);
+            }
+        }
+        assert_eq!(map.reachable().count(), distances.reachable().len());
+    }
+}
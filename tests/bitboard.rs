@@ -0,0 +1,73 @@
+use std::str::FromStr;
+use solve_sokoban::bitboard::BitBoard;
+use solve_sokoban::{Input, Map, Move};
+
+fn load(level: &str) -> Map {
+    Map::from(Input::from_str(level).unwrap())
+}
+
+#[test]
+fn test_shifted_across_words() {
+    let mut board = BitBoard::new(200);
+    board.insert(0);
+    board.insert(63);
+    board.insert(130);
+
+    assert_eq!(vec![1, 64, 131], board.shifted(1).iter().collect::<Vec<_>>());
+    assert_eq!(vec![70, 133], board.shifted(70).iter().collect::<Vec<_>>());
+    assert_eq!(vec![62, 129], board.shifted(-1).iter().collect::<Vec<_>>());
+    assert_eq!(vec![60], board.shifted(-70).iter().collect::<Vec<_>>());
+}
+
+#[test]
+fn test_not_stays_in_board() {
+    let board = !&BitBoard::new(70);
+    assert_eq!(70, board.count());
+    assert_eq!(BitBoard::full(70), board);
+}
+
+#[test]
+fn test_reachable() {
+    let map = load(include_str!("ttac2021/level1.txt"));
+    let reachable = map.reachable();
+    for y in 0..map.height() as u16 {
+        for x in 0..map.width() as u16 {
+            let pos = solve_sokoban::Pos::new(x, y);
+            if map.is_wall(pos) || map.is_box(pos) {
+                assert!(!reachable.contains(map.square(pos).unwrap()));
+            }
+        }
+    }
+    assert!(reachable.contains(map.square(map.player()).unwrap()));
+    assert_eq!(25, reachable.count());
+}
+
+#[test]
+fn test_possible_moves_match_naive_generation() {
+    for level in [
+        include_str!("ttac2021/level1.txt"),
+        include_str!("ttac2021/level2.txt"),
+        include_str!("ttac2021/level3.txt"),
+    ] {
+        let map = load(level);
+        let mut expected = vec![];
+        for b in map.boxes() {
+            for (from, to) in [
+                (b.up(), b.down()),
+                (b.down(), b.up()),
+                (b.right(), b.left()),
+                (b.left(), b.right()),
+            ] {
+                if map.is_free(from) && map.is_push_target(to) {
+                    expected.push(Move::new(b, to));
+                }
+            }
+        }
+
+        let key = |m: &Move| (m.start, m.end);
+        let mut actual = map.possible_moves();
+        actual.sort_by_key(key);
+        expected.sort_by_key(key);
+        assert_eq!(expected, actual);
+    }
+}
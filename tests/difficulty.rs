@@ -0,0 +1,46 @@
+use solve_sokoban::difficulty::DifficultyReport;
+use solve_sokoban::{Costs, Input};
+use std::str::FromStr;
+use std::time::Duration;
+
+fn measure(level: &str, max_states: usize) -> DifficultyReport {
+    let input = Input::from_str(level).unwrap();
+    DifficultyReport::measure(&input, Duration::from_secs(60), max_states).unwrap()
+}
+
+#[test]
+fn test_single_line() {
+    let report = measure("#######\n#@$  .#\n#     #\n#######", 10_000);
+    assert_eq!(Costs::new(3, 3), report.costs);
+    assert_eq!(1, report.box_lines);
+    assert_eq!(1, report.box_changes);
+    assert_eq!(Some(1), report.optimal_solutions);
+    // pushing the box down ends at the wall
+    assert_eq!(1, report.deadlock_decisions);
+    assert!(report.dead_end_ratio.unwrap() > 0.0);
+}
+
+#[test]
+fn test_symmetric_choices() {
+    // right then down or down then right
+    let report = measure("#####\n#@  #\n# $ #\n#  .#\n#####", 100_000);
+    assert_eq!(Costs::new(2, 5), report.costs);
+    assert_eq!(2, report.box_lines);
+    assert_eq!(1, report.box_changes);
+    assert_eq!(Some(2), report.optimal_solutions);
+}
+
+#[test]
+fn test_ttac_levels() {
+    let report = measure(include_str!("ttac2021/level1.txt"), 1_000_000);
+    assert_eq!(Costs::new(12, 26), report.costs);
+    assert!(report.expanded > 0 && report.branching > 0.0);
+    assert!(report.box_changes <= report.box_lines);
+    assert!(report.optimal_solutions.unwrap() >= 1);
+    let ratio = report.dead_end_ratio.unwrap();
+    assert!((0.0..1.0).contains(&ratio));
+
+    let report = measure(include_str!("ttac2021/level1.txt"), 10);
+    assert_eq!(None, report.optimal_solutions);
+    assert_eq!(None, report.dead_end_ratio);
+}
@@ -0,0 +1,50 @@
+use std::str::FromStr;
+use solve_sokoban::{Direction, Input, Map, Move, Pos};
+
+#[test]
+fn test_screen_orientation() {
+    let pos = Pos::new(3, 3);
+    assert_eq!(Some(Pos::new(3, 2)), pos.step(Direction::Up));
+    assert_eq!(Some(Pos::new(3, 4)), pos.step(Direction::Down));
+    assert_eq!(Some(Pos::new(2, 3)), pos.step(Direction::Left));
+    assert_eq!(Some(Pos::new(4, 3)), pos.step(Direction::Right));
+    assert_eq!(pos.up(), pos.step(Direction::Up).unwrap());
+    assert_eq!(pos.down(), pos.step(Direction::Down).unwrap());
+}
+
+#[test]
+fn test_offsets_match_directions() {
+    let map = Map::from(Input::from_str(include_str!("ttac2021/level2.txt")).unwrap());
+    let pos = map.player();
+    let square = map.square(pos).unwrap();
+    for direction in Direction::ALL {
+        let offset = map.offsets()[direction.index()];
+        assert_eq!(
+            map.square(pos.step(direction).unwrap()),
+            Some(square.wrapping_add_signed(offset))
+        );
+    }
+}
+
+#[test]
+fn test_moves_carry_direction() {
+    let map = Map::from(Input::from_str(include_str!("ttac2021/level1.txt")).unwrap());
+    for m in map.possible_moves() {
+        assert_eq!(Some(m.end), m.start.step(m.direction));
+        assert_eq!(m, Move::new(m.start, m.end));
+        assert!(map.is_free(m.player_pos().unwrap()));
+    }
+
+    // player is left of the box in the fifth row
+    let m = Move::in_direction(Pos::new(2, 4), Direction::Right).unwrap();
+    assert_eq!(Some(Pos::new(1, 4)), m.player_pos());
+}
+
+#[test]
+fn test_lurd() {
+    let lurd: String = Direction::ALL.iter().map(|d| d.to_lurd(false)).collect();
+    assert_eq!("udlr", lurd);
+    assert_eq!('R', Direction::Right.to_lurd(true));
+    assert_eq!(Some((Direction::Left, true)), Direction::from_lurd('L'));
+    assert_eq!(None, Direction::from_lurd('x'));
+}
@@ -0,0 +1,47 @@
+use std::str::FromStr;
+use solve_sokoban::floor::NO_FLOOR;
+use solve_sokoban::{Input, Map, Move, Pos};
+
+fn load(level: &str) -> Map {
+    Map::from(Input::from_str(level).unwrap())
+}
+
+#[test]
+fn test_numbers_floor_only() {
+    let map = load(include_str!("ttac2021/level1.txt"));
+    assert_eq!(28, map.floor().len());
+
+    let map = load("   \n #####\n #@$.#\n #####\n   ");
+    assert_eq!(3, map.floor().len());
+    assert_eq!(None, map.floor().index(map.square(Pos::new(0, 2)).unwrap()));
+}
+
+#[test]
+fn test_neighbors() {
+    let map = load(" #####\n #@$.#\n #####");
+    let floor = map.floor();
+    let player = floor.index(map.square(Pos::new(2, 1)).unwrap()).unwrap();
+    let player_box = floor.index(map.square(Pos::new(3, 1)).unwrap()).unwrap();
+    assert_eq!([NO_FLOOR, NO_FLOOR, NO_FLOOR, player_box], floor.neighbors(player));
+}
+
+#[test]
+fn test_transpositions_are_equal() {
+    let start = load(include_str!("ttac2021/level3.txt"));
+    let a = Move::new(Pos::new(2, 3), Pos::new(2, 4));
+    let b = Move::new(Pos::new(4, 3), Pos::new(5, 3));
+
+    let mut ab = start.clone();
+    ab.apply_move(a);
+    ab.apply_move(b);
+
+    let mut ba = start.clone();
+    ba.apply_move(b);
+    ba.apply_move(a);
+    ba.set_player_pos(ab.player());
+
+    assert!(ab.solve_state() == ba.solve_state());
+    let mut set = std::collections::HashSet::new();
+    set.insert(ab.solve_state().clone());
+    assert!(set.contains(ba.solve_state()));
+}
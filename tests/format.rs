@@ -0,0 +1,88 @@
+use std::str::FromStr;
+use solve_sokoban::solver::Solver;
+use solve_sokoban::{Input, InputError, Map, Move, Pos, StateError};
+
+const LEVELS: [&str; 3] = [
+    include_str!("ttac2021/level1.txt"),
+    include_str!("ttac2021/level2.txt"),
+    include_str!("ttac2021/level3.txt"),
+];
+
+#[test]
+fn test_xsb_round_trip() {
+    for level in LEVELS {
+        let input = Input::from_str(level).unwrap();
+        assert_eq!(level.trim_end(), input.to_string());
+        assert_eq!(input, Input::from_str(&input.to_string()).unwrap());
+
+        let map = Map::from(input);
+        assert_eq!(level.trim_end(), map.to_xsb());
+    }
+}
+
+#[test]
+fn test_rle_round_trip() {
+    for level in LEVELS {
+        let input = Input::from_str(level).unwrap();
+        let rle = input.to_rle();
+        assert!(!rle.contains('\n'));
+        assert_eq!(input.to_string(), Input::from_rle(&rle).unwrap().to_string());
+    }
+}
+
+#[test]
+fn test_rle_decoding() {
+    let input = Input::from_rle("6#|#@$-.#|#-*+-#|6#").unwrap();
+    assert_eq!("######\n#@$ .#\n# *+ #\n######", input.to_string());
+    assert_eq!("6#|#@$-.#|#-*+-#|6#", input.to_rle());
+
+    assert!(Input::from_rle("4#|#@$3").is_err());
+    assert!(Input::from_rle("4#|#@x.#").is_err());
+}
+
+#[test]
+fn test_rle_size_limit() {
+    assert!(Input::from_rle("65536#").is_ok());
+    for rle in ["65537#", "99999999999#", "99999999999999999999999#", "#@$.|65536-#"] {
+        assert!(matches!(
+            Input::from_rle(rle),
+            Err(InputError::TooLarge { .. })
+        ));
+    }
+}
+
+#[test]
+fn test_xsb_of_state() {
+    let map = Map::from(Input::from_str("######\n#@$ .#\n######").unwrap());
+    let start = map.solve_state().clone();
+
+    let mut solved = map.clone();
+    solved.apply_move(Move::new(Pos::new(2, 1), Pos::new(3, 1)));
+    solved.apply_move(Move::new(Pos::new(3, 1), Pos::new(4, 1)));
+    assert_eq!("######\n#  @*#\n######", solved.to_xsb());
+    assert_eq!(
+        Ok("######\n#@$ .#\n######".to_string()),
+        solved.to_xsb_with_state(&start)
+    );
+
+    let other = Map::from(Input::from_str("#####\n#@$.#\n#####").unwrap());
+    assert_eq!(
+        Err(StateError::ForeignState),
+        solved.to_xsb_with_state(other.solve_state())
+    );
+}
+
+#[test]
+fn test_lurd_of_solution() {
+    for level in LEVELS {
+        let map = Map::from(Input::from_str(level).unwrap());
+        let solution = Solver::new().solve(&map).unwrap();
+        let lurd = solution.to_lurd(&map).unwrap();
+        let costs = solution.costs();
+        assert_eq!(costs.moves as usize, lurd.len());
+        assert_eq!(
+            costs.pushes as usize,
+            lurd.chars().filter(|c| c.is_ascii_uppercase()).count()
+        );
+    }
+}
@@ -0,0 +1,50 @@
+use std::str::FromStr;
+use std::time::Duration;
+use solve_sokoban::game::{Game, Step};
+use solve_sokoban::{Costs, Direction, Input, Map, Pos};
+
+fn load(level: &str) -> Map {
+    Map::from(Input::from_str(level).unwrap())
+}
+
+#[test]
+fn test_play_undo_redo_restart() {
+    let mut game = Game::new(load("#######\n#@ $ .#\n#     #\n#######"));
+    assert_eq!(Step::Blocked, game.step(Direction::Up));
+    assert_eq!(Step::Walk, game.step(Direction::Right));
+    assert_eq!(Step::Push, game.step(Direction::Right));
+    assert_eq!(Costs::new(1, 2), game.costs());
+    assert_eq!("rR", game.lurd());
+
+    assert!(game.undo());
+    assert_eq!(Costs::new(0, 1), game.costs());
+    assert!(game.map().is_box(Pos::new(3, 1)));
+    assert!(game.redo());
+    assert!(game.map().is_box(Pos::new(4, 1)));
+    assert!(!game.redo());
+
+    game.restart();
+    assert_eq!(Costs::zero(), game.costs());
+    assert_eq!(Pos::new(1, 1), game.map().player());
+    assert!(game.redo() && game.redo());
+    assert_eq!(Step::Push, game.step(Direction::Right));
+    assert!(game.is_solved());
+    assert!(!game.redo());
+}
+
+#[test]
+fn test_deadlock_warnings() {
+    let mut game = Game::new(load("######\n#    #\n# @$ #\n#   .#\n######"));
+    assert!(game.deadlocks().is_empty());
+    assert_eq!(Step::Walk, game.step(Direction::Down));
+    assert_eq!(Step::Walk, game.step(Direction::Right));
+    assert_eq!(Step::Push, game.step(Direction::Up));
+    assert_eq!(vec![Pos::new(3, 1)], game.deadlocks());
+}
+
+#[test]
+fn test_hint() {
+    let game = Game::new(load(include_str!("ttac2021/level1.txt")));
+    let hint = game.hint(Duration::from_secs(60)).unwrap();
+    assert!(game.map().possible_moves().contains(&hint));
+}
@@ -0,0 +1,37 @@
+use solve_sokoban::generator::{Generator, GeneratorConfig};
+use solve_sokoban::{Input, Map};
+use std::str::FromStr;
+
+fn config(seed: u64) -> GeneratorConfig {
+    GeneratorConfig {
+        seed,
+        ..GeneratorConfig::default()
+    }
+}
+
+#[test]
+fn test_generated_level_is_solvable() {
+    let level = Generator::new(config(7)).generate().unwrap();
+    assert!(level.score.pushes >= 10);
+    assert_eq!(level.score.pushes, level.solution.costs().pushes);
+    assert!(level.score.expanded > 0 && level.score.branching > 0.0);
+
+    let mut map = Map::from(Input::from_str(&level.xsb()).unwrap());
+    assert_eq!(3, map.box_count());
+    for m in level.solution.moves() {
+        assert!(map.possible_moves().contains(m));
+        map.apply_move(*m);
+    }
+    assert!(map.is_solved());
+}
+
+#[test]
+fn test_generator_is_deterministic() {
+    let first = Generator::new(config(42)).generate().unwrap();
+    let second = Generator::new(config(42)).generate().unwrap();
+    assert_eq!(first.xsb(), second.xsb());
+    assert_eq!(first.score, second.score);
+
+    let other = Generator::new(config(43)).generate().unwrap();
+    assert_ne!(first.xsb(), other.xsb());
+}
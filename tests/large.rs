@@ -0,0 +1,72 @@
+use std::str::FromStr;
+use solve_sokoban::solver::Solver;
+use solve_sokoban::{Costs, Input, InputError, Map, Pos};
+
+/// Room of three rows and `length` columns with the box right of the player and the
+/// destination at the far end of the middle row.
+fn corridor(length: usize) -> String {
+    let wall = "#".repeat(length + 2);
+    let empty = format!("#{}#", " ".repeat(length));
+    format!("{wall}\n{empty}\n#@${}.#\n{empty}\n{wall}", " ".repeat(length - 3))
+}
+
+#[test]
+fn test_wide_level() {
+    let map = Map::from(Input::from_str(&corridor(300)).unwrap());
+    assert_eq!(302, map.width());
+    assert!(map.is_destination(Pos::new(300, 2)));
+
+    let solution = Solver::new().solve(&map).unwrap();
+    assert_eq!(Costs::new(298, 298), solution.costs());
+}
+
+#[test]
+fn test_high_level() {
+    let lines: Vec<String> = corridor(300)
+        .lines()
+        .map(|line| line.chars().collect())
+        .collect();
+    let mut level = String::new();
+    for x in 0..lines[0].len() {
+        for line in &lines {
+            level.push(line.chars().nth(x).unwrap());
+        }
+        level.push('\n');
+    }
+    let map = Map::from(Input::from_str(level.trim_end()).unwrap());
+    assert_eq!(302, map.height());
+
+    let solution = Solver::new().solve(&map).unwrap();
+    assert_eq!(Costs::new(298, 298), solution.costs());
+}
+
+#[test]
+fn test_level_at_old_limit() {
+    let map = Map::from(Input::from_str(&corridor(254)).unwrap());
+    assert_eq!(256, map.width());
+    let solution = Solver::new().solve(&map).unwrap();
+    assert_eq!(252, solution.costs().pushes);
+}
+
+#[test]
+fn test_too_large_level() {
+    let level = "#".repeat(u16::MAX as usize + 2);
+    assert!(matches!(
+        Input::from_str(&level),
+        Err(InputError::TooLarge { .. })
+    ));
+}
+
+#[test]
+fn test_checked_neighbors() {
+    let origin = Pos::new(0, 0);
+    assert_eq!(None, origin.checked_left());
+    assert_eq!(None, origin.checked_up());
+    assert_eq!(Some(Pos::new(1, 0)), origin.checked_right());
+    assert_eq!(Some(Pos::new(0, 1)), origin.checked_down());
+    assert_eq!(2, origin.neighbors().count());
+
+    let max = Pos::new(u16::MAX, u16::MAX);
+    assert_eq!(None, max.checked_right());
+    assert_eq!(None, max.checked_down());
+}
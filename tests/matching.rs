@@ -0,0 +1,86 @@
+use solve_sokoban::solver::{Heuristic, Solver, SolverConfig, SolverStats, Strategy};
+use solve_sokoban::{Input, Map, Solution};
+use std::str::FromStr;
+
+fn load(level: &str) -> Map {
+    Map::from(Input::from_str(level).unwrap())
+}
+
+fn solve(map: &Map, heuristic: Heuristic) -> (Option<Solution>, SolverStats) {
+    let config = SolverConfig {
+        strategy: Strategy::AStar,
+        heuristic,
+        ..SolverConfig::default()
+    };
+    Solver::with_config(config).solve_with_stats(map)
+}
+
+#[test]
+fn test_matching_distance() {
+    // both boxes are nearest to the same destination
+    let map = load(
+        "########\n\
+         #@     #\n\
+         # $ $..#\n\
+         #      #\n\
+         ########",
+    );
+    assert_eq!(4, map.destination_distance());
+    assert_eq!(Some(5), map.matching_distance());
+}
+
+#[test]
+fn test_matching_finds_optimal_pushes() {
+    for level in [
+        include_str!("ttac2021/level1.txt"),
+        include_str!("ttac2021/level2.txt"),
+        include_str!("ttac2021/level3.txt"),
+    ] {
+        let map = load(level);
+        let optimal = Solver::new().solve(&map).unwrap().costs();
+        let (distance, distance_stats) = solve(&map, Heuristic::Distance);
+        let (matching, matching_stats) = solve(&map, Heuristic::Matching);
+        assert_eq!(optimal.pushes, distance.unwrap().costs().pushes);
+        assert_eq!(optimal.pushes, matching.unwrap().costs().pushes);
+        assert!(matching_stats.expanded <= distance_stats.expanded);
+    }
+}
+
+#[test]
+fn test_matching_deadlock() {
+    // no box can be pushed up onto the upper destination
+    let map = load(
+        "#######\n\
+         ####.##\n\
+         #@    #\n\
+         # $$#.#\n\
+         #     #\n\
+         #######",
+    );
+    assert_eq!(None, map.matching_distance());
+
+    let (solution, stats) = solve(&map, Heuristic::Matching);
+    assert!(solution.is_none());
+    assert_eq!(1, stats.expanded);
+    let (solution, stats) = solve(&map, Heuristic::Distance);
+    assert!(solution.is_none());
+    assert!(stats.expanded > 1);
+}
+
+#[test]
+fn test_matching_with_more_destinations() {
+    // one destination stays free
+    let map = load(
+        "#########\n\
+         #.  @   #\n\
+         # $  $. #\n\
+         #     . #\n\
+         #########",
+    );
+    assert_eq!(Some(3), map.matching_distance());
+
+    let (solution, stats) = solve(&map, Heuristic::Matching);
+    assert!(solution.is_none());
+    let (_, distance_stats) = solve(&map, Heuristic::Distance);
+    assert!(stats.expanded <= distance_stats.expanded);
+}
@@ -0,0 +1,93 @@
+use solve_sokoban::optimizer::Optimizer;
+use solve_sokoban::solver::{Solver, SolverConfig, Strategy};
+use solve_sokoban::{Costs, Direction, Input, Map, Move, Pos};
+use std::str::FromStr;
+
+const LEVELS: [&str; 3] = [
+    include_str!("ttac2021/level1.txt"),
+    include_str!("ttac2021/level2.txt"),
+    include_str!("ttac2021/level3.txt"),
+];
+
+fn load(level: &str) -> Map {
+    Map::from(Input::from_str(level).unwrap())
+}
+
+#[test]
+fn test_removes_detour() {
+    // pushing the box back and forth before solving
+    let map = load("#######\n#     #\n#@$  .#\n#     #\n#######");
+    let moves = [
+        Move::new(Pos::new(2, 2), Pos::new(3, 2)),
+        Move::new(Pos::new(3, 2), Pos::new(2, 2)),
+        Move::new(Pos::new(2, 2), Pos::new(3, 2)),
+        Move::new(Pos::new(3, 2), Pos::new(4, 2)),
+        Move::new(Pos::new(4, 2), Pos::new(5, 2)),
+    ];
+    let optimized = Optimizer::new().optimize(&map, &moves).unwrap();
+    assert_eq!(Costs::new(3, 3), optimized.solution.costs());
+    assert_eq!((2, 10), optimized.improvement());
+    assert_eq!(3, optimized.solution.moves().len());
+}
+
+/// Pushes of a solution in LURD notation.
+fn pushes(map: &Map, lurd: &str) -> Vec<Move> {
+    let mut player = map.player();
+    let mut moves = vec![];
+    for c in lurd.chars() {
+        let (direction, push) = Direction::from_lurd(c).unwrap();
+        player = player.step(direction).unwrap();
+        if push {
+            moves.push(Move::in_direction(player, direction).unwrap());
+        }
+    }
+    moves
+}
+
+#[test]
+fn test_fewer_pushes_with_more_moves() {
+    let map = load(
+        "########\n\
+         #   .  #\n\
+         # $#   #\n\
+         #@  #  #\n\
+         # ###  #\n\
+         #  .$ ##\n\
+         ########",
+    );
+    let moves = pushes(&map, "rUluRRRdrruLddddL");
+    let optimized = Optimizer::new().optimize(&map, &moves).unwrap();
+    assert_eq!(Costs::new(6, 17), optimized.before);
+    assert_eq!(Costs::new(4, 28), optimized.solution.costs());
+    assert_eq!((2, -11), optimized.improvement());
+}
+
+#[test]
+fn test_improves_greedy_solutions() {
+    for level in LEVELS {
+        let map = load(level);
+        let config = SolverConfig {
+            strategy: Strategy::Greedy,
+            ..SolverConfig::default()
+        };
+        let greedy = Solver::with_config(config).solve(&map).unwrap();
+        let optimal = Solver::new().solve(&map).unwrap().costs();
+
+        let optimized = Optimizer::new()
+            .with_window(6)
+            .optimize(&map, greedy.moves())
+            .unwrap();
+        let costs = optimized.solution.costs();
+        assert!(costs <= greedy.costs());
+        assert!(costs >= optimal);
+        assert_eq!(greedy.costs(), optimized.before);
+    }
+}
+
+#[test]
+fn test_rejects_invalid_solutions() {
+    let map = load(LEVELS[0]);
+    let solution = Solver::new().solve(&map).unwrap();
+    let moves = &solution.moves()[..solution.moves().len() - 1];
+    assert!(Optimizer::new().optimize(&map, moves).is_none());
+}
@@ -0,0 +1,96 @@
+use solve_sokoban::oracle::{Answer, Oracle};
+use solve_sokoban::solver::Solver;
+use solve_sokoban::{Costs, Input, Map, Pos};
+use std::str::FromStr;
+
+fn load(level: &str) -> Map {
+    Map::from(Input::from_str(level).unwrap())
+}
+
+fn costs(answer: Answer) -> Costs {
+    match answer {
+        Answer::Solvable(solution) => solution.costs(),
+        other => panic!("not solvable: {:?}", other),
+    }
+}
+
+#[test]
+fn test_answers_rest_of_solution_from_knowledge() {
+    let map = load(include_str!("ttac2021/level1.txt"));
+    let mut oracle = Oracle::new(&map);
+
+    let solution = match oracle.query_map(&map) {
+        Answer::Solvable(solution) => solution,
+        other => panic!("not solvable: {:?}", other),
+    };
+    assert_eq!(Costs::new(12, 26), solution.costs());
+    assert!(oracle.last_stats().expanded > 0);
+    assert!(oracle.known_states() > solution.moves().len());
+
+    let mut state = map.clone();
+    for m in &solution.moves()[..4] {
+        state.apply_move(*m);
+    }
+    let rest = costs(oracle.query(state.solve_state()).unwrap());
+    assert_eq!(8, rest.pushes);
+    assert_eq!(0, oracle.last_stats().expanded);
+    assert_eq!(Some(rest), Solver::new().solve(&state).map(|s| s.costs()));
+}
+
+#[test]
+fn test_new_queries_use_knowledge() {
+    let map = load(include_str!("ttac2021/level2.txt"));
+    let mut oracle = Oracle::new(&map);
+    oracle.query_map(&map);
+    let first = oracle.last_stats().expanded;
+
+    // same boxes, but the player starts one step away
+    let player = map.player();
+    let moved = [player.up(), player.down(), player.left(), player.right()]
+        .into_iter()
+        .find_map(|pos| map.with_positions(pos, map.boxes()).ok())
+        .unwrap();
+    let expected = Solver::new().solve(&moved).unwrap().costs();
+    assert_eq!(expected, costs(oracle.query_map(&moved)));
+    assert!(oracle.last_stats().expanded < first);
+}
+
+#[test]
+fn test_unsolvable_states_are_remembered() {
+    let map = load("######\n#    #\n# @$ #\n#   .#\n######");
+    let mut oracle = Oracle::new(&map);
+    assert_eq!(Some(true), oracle.is_solvable(&map));
+
+    let stuck = map
+        .with_positions(Pos::new(3, 2), [Pos::new(1, 1)])
+        .unwrap();
+    assert_eq!(Some(false), oracle.is_solvable(&stuck));
+    assert_eq!(None, oracle.remaining_costs(&stuck));
+    assert_eq!(0, oracle.last_stats().expanded);
+
+    let foreign = load(include_str!("ttac2021/level1.txt"));
+    assert!(oracle.query(foreign.solve_state()).is_err());
+}
+
+#[test]
+fn test_earlier_queries_do_not_prune_later_ones() {
+    let map = load(include_str!("ttac2021/level3.txt"));
+    let mut states = vec![map.clone()];
+    for _ in 0..3 {
+        let mut state = states.last().unwrap().clone();
+        let m = *state.reachable_moves().last().unwrap();
+        state.apply_move(m);
+        states.push(state);
+    }
+
+    // later states first, so that earlier queries expand states closer to their start
+    let mut oracle = Oracle::new(&map);
+    for state in states.iter().rev() {
+        let expected = Solver::new().solve(state).map(|s| s.costs());
+        match oracle.query_map(state) {
+            Answer::Solvable(solution) => assert_eq!(expected, Some(solution.costs())),
+            Answer::Unsolvable => assert_eq!(None, expected),
+            Answer::Unknown => unreachable!(),
+        }
+    }
+}
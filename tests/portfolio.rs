@@ -0,0 +1,69 @@
+use std::str::FromStr;
+use std::time::Duration;
+use solve_sokoban::portfolio::{Completion, Portfolio};
+use solve_sokoban::solver::{Solver, SolverConfig, Strategy};
+use solve_sokoban::{Input, Map};
+
+fn load(level: &str) -> Map {
+    Map::from(Input::from_str(level).unwrap())
+}
+
+#[test]
+fn test_first_solution_reports_winner() {
+    let map = load(include_str!("ttac2021/level1.txt"));
+    let portfolio = Portfolio::standard().with_timeout(Duration::from_secs(60));
+    let result = portfolio.solve(&map).unwrap();
+    assert!(portfolio.configs().any(|name| name == result.winner));
+    let names: Vec<&str> = result.stats.iter().map(|(name, _)| name.as_str()).collect();
+    assert_eq!(portfolio.configs().collect::<Vec<_>>(), names);
+    let (_, winner) = result.stats.iter().find(|(name, _)| *name == result.winner).unwrap();
+    assert!(winner.expanded > 0);
+    assert_eq!(12, result.solution.costs().pushes);
+}
+
+#[test]
+fn test_best_solution_is_optimal() {
+    let map = load(include_str!("ttac2021/level3.txt"));
+    let result = Portfolio::standard()
+        .with_completion(Completion::BestSolution)
+        .solve(&map)
+        .unwrap();
+    assert_eq!(13, result.solution.costs().pushes);
+}
+
+#[test]
+fn test_astar_is_push_optimal() {
+    let map = load(include_str!("ttac2021/level2.txt"));
+    let config = SolverConfig {
+        strategy: Strategy::AStar,
+        freeze_deadlocks: true,
+        ..SolverConfig::default()
+    };
+    let solution = Solver::with_config(config).solve(&map).unwrap();
+    assert_eq!(12, solution.costs().pushes);
+}
+
+#[test]
+fn test_expired_deadline() {
+    let map = load(include_str!("ttac2021/level2.txt"));
+    let result = Portfolio::new()
+        .with_config("uniform", SolverConfig::default())
+        .with_timeout(Duration::ZERO)
+        .solve(&map);
+    assert!(result.is_none());
+}
+
+#[test]
+fn test_expansion_budget() {
+    let map = load(include_str!("ttac2021/level2.txt"));
+    for strategy in [Strategy::UniformCost, Strategy::Reverse, Strategy::Bidirectional] {
+        let config = SolverConfig {
+            strategy,
+            max_expanded: Some(10),
+            ..SolverConfig::default()
+        };
+        let (solution, stats) = Solver::with_config(config).solve_with_stats(&map);
+        assert!(solution.is_none());
+        assert_eq!(10, stats.expanded);
+    }
+}
@@ -0,0 +1,72 @@
+use solve_sokoban::{Input, Map, Pos};
+use std::str::FromStr;
+
+fn load(level: &str) -> Map {
+    Map::from(Input::from_str(level).unwrap())
+}
+
+#[test]
+fn test_simple_push_distance() {
+    let map = load(
+        "#######\n\
+         #@    #\n\
+         # $ . #\n\
+         #     #\n\
+         #######",
+    );
+    let destination = Pos::new(4, 2);
+    assert_eq!(Some(2), map.push_distance(Pos::new(2, 2), destination));
+    assert_eq!(Some(1), map.push_distance(Pos::new(3, 2), destination));
+    assert_eq!(Some(0), map.push_distance(destination, destination));
+    // boxes along the walls can not leave them
+    assert_eq!(None, map.push_distance(Pos::new(1, 2), destination));
+    assert_eq!(None, map.push_distance(Pos::new(3, 1), destination));
+    // not a destination
+    assert_eq!(None, map.push_distance(Pos::new(2, 2), Pos::new(3, 2)));
+}
+
+#[test]
+fn test_player_push_distance() {
+    let map = load(
+        "########\n\
+         #   #  #\n\
+         # @ $ .#\n\
+         #   #  #\n\
+         ########",
+    );
+    let from = Pos::new(4, 2);
+    let destination = Pos::new(6, 2);
+    assert_eq!(Some(2), map.push_distance(from, destination));
+    assert_eq!(
+        Some(2),
+        map.player_push_distance(from, Pos::new(1, 1), destination)
+    );
+    // the box blocks the only way to its left side, the player gets there by pushing it two
+    // squares into the left room
+    assert_eq!(
+        Some(6),
+        map.player_push_distance(from, Pos::new(5, 1), destination)
+    );
+    assert_eq!(None, map.player_push_distance(from, from, destination));
+}
+
+#[test]
+fn test_player_aware_distances_are_not_smaller() {
+    let map = load(include_str!("ttac2021/level2.txt"));
+    let distances = map.push_distances();
+    let floor = map.floor();
+    assert_eq!(map.destinations().count(), distances.destinations().len());
+    for destination in 0..distances.destinations().len() {
+        for square in 0..floor.len() as u32 {
+            let simple = distances.simple(destination, square);
+            for side in 0..4 {
+                if let Some(distance) = distances.with_player_side(destination, square, side) {
+                    assert!(simple.is_some_and(|simple| simple <= distance));
+                }
+            }
+        }
+    }
+    for square in distances.destinations() {
+        assert_eq!(Some(0), distances.nearest(*square));
+    }
+}
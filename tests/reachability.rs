@@ -0,0 +1,49 @@
+use std::str::FromStr;
+use solve_sokoban::algos::bfs::DistanceMap;
+use solve_sokoban::algos::reachability::Reachability;
+use solve_sokoban::{Input, Map};
+
+/// Small deterministic pseudo random generator to pick pushes.
+struct Lcg(u64);
+
+impl Lcg {
+    fn next(&mut self, n: usize) -> usize {
+        self.0 = self.0.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        ((self.0 >> 33) % n as u64) as usize
+    }
+}
+
+#[test]
+fn test_incremental_updates_match_recomputation() {
+    let levels = [
+        include_str!("ttac2021/level1.txt"),
+        include_str!("ttac2021/level2.txt"),
+        include_str!("ttac2021/level3.txt"),
+    ];
+    let mut rng = Lcg(42);
+    for level in levels {
+        let start = Map::from(Input::from_str(level).unwrap());
+        for _ in 0..200 {
+            let mut map = start.clone();
+            let mut reachability = Reachability::new(&map);
+            for _ in 0..30 {
+                let moves = map.reachable_moves();
+                if moves.is_empty() {
+                    break;
+                }
+                let m = moves[rng.next(moves.len())];
+                map.apply_move(m);
+                reachability.apply_move(&map, m);
+
+                assert_eq!(&map.reachable(), reachability.region(), "after {:?}\n{}", m, map);
+                let distances = DistanceMap::from_player(&map);
+                for square in 0..map.squares() {
+                    assert_eq!(
+                        distances.distance(square),
+                        reachability.distance(&map, square)
+                    );
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,92 @@
+use std::str::FromStr;
+use std::time::Duration;
+use solve_sokoban::render::{AnsiRenderer, DebugRenderer};
+use solve_sokoban::{Input, Map, Pos};
+
+fn load(level: &str) -> Map {
+    Map::from(Input::from_str(level).unwrap())
+}
+
+#[test]
+fn test_display_is_xsb() {
+    let level = include_str!("ttac2021/level1.txt");
+    assert_eq!(level.trim_end(), load(level).to_string());
+}
+
+#[test]
+fn test_dead_squares() {
+    let map = load("#####\n#@$ #\n#  .#\n#####");
+    assert_eq!(
+        "#####\n#@o~#\n#~ .#\n#####\n",
+        DebugRenderer::new().with_dead_squares(true).render(&map)
+    );
+    assert_eq!(map.debug_view(), DebugRenderer::new().with_dead_squares(true).render(&map));
+}
+
+#[test]
+fn test_reachable_and_corrals() {
+    let map = load("#######\n#@ #  #\n#  $ .#\n#######");
+    assert_eq!(
+        "#######\n#@:#aa#\n#::oa.#\n#######\n",
+        DebugRenderer::new()
+            .with_reachable(true)
+            .with_corrals(true)
+            .render(&map)
+    );
+}
+
+#[test]
+fn test_tunnels_and_frozen_boxes() {
+    let map = load("######\n#    #\n## ###\n#@$$.#\n#  # #\n######");
+    let view = DebugRenderer::new()
+        .with_tunnels(true)
+        .with_frozen_boxes(true)
+        .render(&map);
+    assert_eq!("######\n#= ==#\n##=###\n#@oo.#\n#  #=#\n######\n", view);
+
+    let map = load("####\n#$$#\n#@.#\n# .#\n####");
+    let view = DebugRenderer::new().with_frozen_boxes(true).render(&map);
+    assert_eq!("####\n#XX#\n#@.#\n# .#\n####\n", view);
+}
+
+#[test]
+fn test_heatmap() {
+    let map = load("######\n#@$  #\n#   .#\n######");
+    let view = DebugRenderer::new()
+        .with_heatmap(Some(Pos::new(4, 2)))
+        .render(&map);
+    assert_eq!("######\n#@o!!#\n#!21.#\n######\n", view);
+}
+
+#[test]
+fn test_ansi_colors() {
+    let map = load("#####\n#@$.#\n#####");
+    let plain = AnsiRenderer::new().with_colors(false).render(&map);
+    assert_eq!("#####\n#@$.#\n#####\n", plain);
+
+    let colored = AnsiRenderer::new().render(&map);
+    assert!(colored.contains("\x1b["));
+    let stripped: String = colored
+        .split("\x1b[")
+        .enumerate()
+        .map(|(i, part)| if i == 0 { part } else { &part[part.find('m').unwrap() + 1..] })
+        .collect();
+    assert_eq!(plain, stripped);
+}
+
+#[test]
+fn test_replay() {
+    let map = load("######\n#@ $.#\n######");
+    let renderer = AnsiRenderer::new()
+        .with_colors(false)
+        .with_delay(Duration::ZERO);
+
+    let mut out = vec![];
+    renderer.replay(&map, "rR", &mut out).unwrap();
+    let out = String::from_utf8(out).unwrap();
+    assert!(out.ends_with("######\n#  @*#\n######\nmoves: 2, pushes: 1\n"));
+    assert_eq!(3, out.matches("moves:").count());
+
+    assert!(renderer.replay(&map, "R", &mut vec![]).is_err());
+    assert!(renderer.replay(&map, "l", &mut vec![]).is_err());
+}
@@ -0,0 +1,97 @@
+use solve_sokoban::solver::{Solver, SolverConfig, Strategy};
+use solve_sokoban::{Input, Map, Solution};
+use std::str::FromStr;
+
+fn load(level: &str) -> Map {
+    Map::from(Input::from_str(level).unwrap())
+}
+
+fn solve_reverse(map: &Map) -> Option<Solution> {
+    solve_with(map, Strategy::Reverse)
+}
+
+fn solve_with(map: &Map, strategy: Strategy) -> Option<Solution> {
+    let config = SolverConfig {
+        strategy,
+        ..SolverConfig::default()
+    };
+    Solver::with_config(config).solve_with_stats(map).0
+}
+
+#[test]
+fn test_reverse_finds_push_optimal_solutions() {
+    for level in [
+        include_str!("ttac2021/level1.txt"),
+        include_str!("ttac2021/level2.txt"),
+        include_str!("ttac2021/level3.txt"),
+    ] {
+        let map = load(level);
+        let optimal = Solver::new().solve(&map).unwrap().costs();
+        let solution = solve_reverse(&map).unwrap();
+        assert_eq!(optimal.pushes, solution.costs().pushes);
+        assert!(solution.costs().moves >= optimal.moves);
+
+        let mut replay = map.clone();
+        for m in solution.moves() {
+            assert!(replay.is_box(m.start) && replay.is_free(m.end));
+            replay.apply_move(*m);
+        }
+        assert!(replay.is_solved());
+        let lurd = solution.to_lurd(&map).unwrap();
+        assert_eq!(solution.costs().moves as usize, lurd.len());
+    }
+}
+
+#[test]
+fn test_reverse_small_levels() {
+    let map = load(
+        "#######\n\
+         #@ $ .#\n\
+         #     #\n\
+         #######",
+    );
+    let solution = solve_reverse(&map).unwrap();
+    assert_eq!(2, solution.costs().pushes);
+    assert!(solve_reverse(&load("#####\n#@$.#\n#####")).is_some());
+}
+
+#[test]
+fn test_reverse_unsolvable() {
+    let map = load(
+        "######\n\
+         #@  .#\n\
+         #   $#\n\
+         ######",
+    );
+    let config = SolverConfig {
+        strategy: Strategy::Reverse,
+        ..SolverConfig::default()
+    };
+    let (solution, stats) = Solver::with_config(config).solve_with_stats(&map);
+    assert!(solution.is_none());
+    assert_eq!(0, stats.open);
+}
+
+#[test]
+fn test_bidirectional_finds_push_optimal_solutions() {
+    for level in [
+        include_str!("ttac2021/level1.txt"),
+        include_str!("ttac2021/level2.txt"),
+        include_str!("ttac2021/level3.txt"),
+    ] {
+        let map = load(level);
+        let optimal = Solver::new().solve(&map).unwrap().costs();
+        let solution = solve_with(&map, Strategy::Bidirectional).unwrap();
+        assert_eq!(optimal.pushes, solution.costs().pushes);
+
+        let mut replay = map.clone();
+        for m in solution.moves() {
+            assert!(replay.is_box(m.start) && replay.is_free(m.end));
+            replay.apply_move(*m);
+        }
+        assert!(replay.is_solved());
+    }
+
+    let unsolvable = load("######\n#@  .#\n#   $#\n######");
+    assert!(solve_with(&unsolvable, Strategy::Bidirectional).is_none());
+}
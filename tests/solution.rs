@@ -0,0 +1,51 @@
+use solve_sokoban::solver::{Solver, SolverConfig, TieBreak};
+use solve_sokoban::{Costs, Input, Map, Move, Pos, Solution};
+use std::str::FromStr;
+
+fn load(level: &str) -> Map {
+    Map::from(Input::from_str(level).unwrap())
+}
+
+#[test]
+fn test_solution_statistics() {
+    let map = load("#######\n#     #\n#@$  .#\n#  $ .#\n#######");
+    let moves = vec![
+        Move::new(Pos::new(2, 2), Pos::new(3, 2)),
+        Move::new(Pos::new(3, 2), Pos::new(4, 2)),
+        Move::new(Pos::new(3, 3), Pos::new(4, 3)),
+        Move::new(Pos::new(4, 2), Pos::new(5, 2)),
+        Move::new(Pos::new(4, 3), Pos::new(5, 3)),
+    ];
+    let solution = Solution::new(moves, Costs::new(5, 0));
+    assert_eq!(4, solution.box_lines());
+    assert_eq!(4, solution.box_changes());
+    assert_eq!("RRldRuRldR", solution.to_lurd(&map).unwrap());
+    assert_eq!(Some(9), solution.player_lines(&map));
+}
+
+#[test]
+fn test_straight_solution() {
+    let map = load("######\n#    #\n#@$ .#\n#    #\n######");
+    let solution = Solver::new().solve(&map).unwrap();
+    assert_eq!(1, solution.box_lines());
+    assert_eq!(1, solution.box_changes());
+    assert_eq!(Some(1), solution.player_lines(&map));
+}
+
+#[test]
+fn test_tie_break_keeps_costs() {
+    let map = load(include_str!("ttac2021/level3.txt"));
+    let plain = Solver::new().solve(&map).unwrap();
+    for tie_break in [TieBreak::BoxLines, TieBreak::BoxChanges] {
+        let config = SolverConfig {
+            tie_break,
+            ..SolverConfig::default()
+        };
+        let solution = Solver::with_config(config).solve(&map).unwrap();
+        assert_eq!(plain.costs(), solution.costs());
+        match tie_break {
+            TieBreak::BoxLines => assert!(solution.box_lines() <= plain.box_lines()),
+            _ => assert!(solution.box_changes() <= plain.box_changes()),
+        }
+    }
+}
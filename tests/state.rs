@@ -0,0 +1,90 @@
+use solve_sokoban::solver::Solver;
+use solve_sokoban::{Costs, Input, Map, Pos, StateError};
+use std::str::FromStr;
+
+const LEVEL: &str = "\
+#######
+#@ $ .#
+#   $.#
+#######";
+
+fn load(level: &str) -> Map {
+    Map::from(Input::from_str(level).unwrap())
+}
+
+#[test]
+fn test_solve_from_mid_game_state() {
+    let map = load(LEVEL);
+    let state = map
+        .with_positions(Pos::new(3, 1), [Pos::new(4, 1), Pos::new(5, 2)])
+        .unwrap();
+    assert!(!state.is_solved());
+    assert_eq!(Pos::new(3, 1), state.player());
+
+    let solution = Solver::new().solve(&state).unwrap();
+    assert_eq!(Costs::new(1, 1), solution.costs());
+
+    let solved = map
+        .with_positions(Pos::new(1, 1), [Pos::new(5, 1), Pos::new(5, 2)])
+        .unwrap();
+    assert!(solved.is_solved());
+}
+
+#[test]
+fn test_invalid_states() {
+    let map = load(LEVEL);
+    let boxes = [Pos::new(3, 1), Pos::new(4, 2)];
+    assert_eq!(
+        Some(StateError::PlayerOffFloor(Pos::new(0, 0))),
+        map.with_positions(Pos::new(0, 0), boxes).err()
+    );
+    assert_eq!(
+        Some(StateError::PlayerOffFloor(Pos::new(9, 9))),
+        map.with_positions(Pos::new(9, 9), boxes).err()
+    );
+    assert_eq!(
+        Some(StateError::PlayerOnBox(Pos::new(3, 1))),
+        map.with_positions(Pos::new(3, 1), boxes).err()
+    );
+    assert_eq!(
+        Some(StateError::BoxOffFloor(Pos::new(6, 1))),
+        map.with_positions(Pos::new(1, 1), [Pos::new(6, 1), Pos::new(4, 2)])
+            .err()
+    );
+    assert_eq!(
+        Some(StateError::DuplicateBox(Pos::new(3, 1))),
+        map.with_positions(Pos::new(1, 1), [Pos::new(3, 1), Pos::new(3, 1)])
+            .err()
+    );
+    assert_eq!(
+        Some(StateError::BoxCount {
+            boxes: 1,
+            destinations: 2
+        }),
+        map.with_positions(Pos::new(1, 1), [Pos::new(3, 1)]).err()
+    );
+}
+
+#[test]
+fn test_state_of_other_map() {
+    let map = load(LEVEL);
+    let moved = map
+        .with_positions(Pos::new(2, 2), [Pos::new(3, 1), Pos::new(3, 2)])
+        .unwrap();
+    let copy = map.with_checked_solve_state(moved.solve_state()).unwrap();
+    assert_eq!(moved.solve_state(), copy.solve_state());
+
+    let other = load(include_str!("ttac2021/level1.txt"));
+    assert_eq!(
+        Some(StateError::ForeignState),
+        map.with_checked_solve_state(other.solve_state()).err()
+    );
+
+    // all floor indices of the smaller level are valid in the larger one
+    let small = load("#####\n#@$.#\n#####");
+    let large = load("######\n#@$ .#\n######");
+    assert_eq!(
+        Some(StateError::ForeignState),
+        large.with_checked_solve_state(small.solve_state()).err()
+    );
+}
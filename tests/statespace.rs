@@ -0,0 +1,58 @@
+use solve_sokoban::solver::Solver;
+use solve_sokoban::statespace::StateSpace;
+use solve_sokoban::{Costs, Input, Map, Pos};
+use std::str::FromStr;
+
+fn load(level: &str) -> Map {
+    Map::from(Input::from_str(level).unwrap())
+}
+
+#[test]
+fn test_exact_costs_match_solver() {
+    let map = load(include_str!("ttac2021/level1.txt"));
+    let space = StateSpace::enumerate(&map, 1_000_000).unwrap();
+    assert_eq!(Some(Costs::new(12, 26)), space.remaining(map.solve_state()));
+
+    let solution = space.solution(&map).unwrap();
+    assert_eq!(Costs::new(12, 26), solution.costs());
+    let mut solved = map.clone();
+    for m in solution.moves() {
+        assert!(solved.possible_moves().contains(m));
+        solved.apply_move(*m);
+    }
+    assert!(solved.is_solved());
+    assert_eq!(12, solution.moves().len());
+}
+
+#[test]
+fn test_proves_unsolvable() {
+    let map = load("######\n#    #\n# @$ #\n#   .#\n######");
+    let space = StateSpace::enumerate(&map, 1000).unwrap();
+    assert!(space.is_solvable(&map));
+    assert_eq!(
+        Solver::new().solve(&map).map(|s| s.costs()),
+        space.remaining(map.solve_state())
+    );
+
+    let stuck = map
+        .with_positions(Pos::new(2, 2), [Pos::new(4, 1)])
+        .unwrap();
+    assert!(!space.is_solvable(&stuck));
+    assert!(space.best_move(&stuck).is_none());
+
+    assert!(StateSpace::enumerate(&map, 10).is_none());
+}
+
+#[test]
+fn test_validates_heuristic_and_deadlocks() {
+    let map = load(include_str!("ttac2021/level1.txt"));
+    let space = StateSpace::enumerate(&map, 1_000_000).unwrap();
+    for (state, costs) in space.iter() {
+        let map = map.with_checked_solve_state(state).unwrap();
+        assert!(map.destination_distance() <= costs.pushes);
+        for pos in map.boxes() {
+            assert!(map.is_destination(pos) || !map.is_dead(pos), "{}", pos);
+            assert!(!map.is_freeze_deadlock(pos));
+        }
+    }
+}
@@ -0,0 +1,54 @@
+use std::str::FromStr;
+use solve_sokoban::solver::Solver;
+use solve_sokoban::transform::Transform;
+use solve_sokoban::{Costs, Input, Map, Move, Pos};
+
+const MIRRORED: &str = "\
+#######
+#.   .#
+#     #
+# $ $ #
+#  @  #
+#######";
+
+fn load(level: &str) -> Map {
+    Map::from(Input::from_str(level).unwrap())
+}
+
+#[test]
+fn test_detects_mirror_symmetry() {
+    let map = load(MIRRORED);
+    assert_eq!(
+        vec![Transform::FlipHorizontal],
+        map.symmetries().collect::<Vec<_>>()
+    );
+
+    let map = load(include_str!("ttac2021/level1.txt"));
+    assert_eq!(0, map.symmetries().count());
+}
+
+#[test]
+fn test_detects_full_symmetry() {
+    let map = load("#####\n#. .#\n# @ #\n#. .#\n#####");
+    assert_eq!(7, map.symmetries().count());
+}
+
+#[test]
+fn test_mirrored_states_are_canonically_equal() {
+    let map = load(MIRRORED);
+
+    let mut left = map.clone();
+    left.apply_move(Move::new(Pos::new(2, 3), Pos::new(2, 2)));
+    let mut right = map.clone();
+    right.apply_move(Move::new(Pos::new(4, 3), Pos::new(4, 2)));
+
+    assert!(left.solve_state() != right.solve_state());
+    assert!(left.canonical_solve_state() == right.canonical_solve_state());
+}
+
+#[test]
+fn test_solves_symmetric_level_optimally() {
+    let map = load(MIRRORED);
+    let solution = Solver::new().solve(&map).unwrap();
+    assert_eq!(Costs::new(6, 14), solution.costs());
+}
@@ -0,0 +1,78 @@
+use std::str::FromStr;
+use solve_sokoban::solver::Solver;
+use solve_sokoban::transform::{BoardMapping, Transform};
+use solve_sokoban::{Input, Map, Pos};
+
+const DECORATED: &str = "
+   $  .
+   #####
+   #@ $.#
+   #####
+";
+
+fn load(level: &str) -> Map {
+    Map::from(Input::from_str(level).unwrap())
+}
+
+/// Applies all moves of a solution and checks that every move is a valid push.
+fn replay(map: &Map, solution: &solve_sokoban::Solution) -> bool {
+    let mut map = map.clone();
+    for m in solution.moves() {
+        if !map.possible_moves().contains(m) {
+            return false;
+        }
+        map.apply_move(*m);
+    }
+    map.is_solved()
+}
+
+#[test]
+fn test_transformed_solution_solves_transformed_level() {
+    let map = load(include_str!("ttac2021/level1.txt"));
+    let solution = Solver::new().solve(&map).unwrap();
+
+    for transform in Transform::ALL {
+        let (transformed, mapping) = map.transformed(transform);
+        let mapped = mapping.map_solution(&solution).unwrap();
+        assert_eq!(solution.costs(), mapped.costs());
+        assert!(replay(&transformed, &mapped), "{:?}", transform);
+
+        let back = mapping.inverse().map_solution(&mapped).unwrap();
+        assert_eq!(solution.moves(), back.moves());
+    }
+}
+
+#[test]
+fn test_transform_round_trip() {
+    let (input, _) = Input::from_str(include_str!("ttac2021/level2.txt"))
+        .unwrap()
+        .normalized();
+    for transform in Transform::ALL {
+        let (transformed, mapping) = input.transformed(transform);
+        let (back, back_mapping) = transformed.transformed(transform.inverse());
+        assert_eq!(input, back, "{:?}", transform);
+        assert_eq!(BoardMapping::identity(), mapping.then(back_mapping));
+    }
+}
+
+#[test]
+fn test_normalize_removes_decoration() {
+    let input = Input::from_str(DECORATED).unwrap();
+    let (normalized, mapping) = input.normalized();
+    assert_eq!(
+        Input::from_str("#####\n#@ $.#\n#####").unwrap().padded(),
+        normalized
+    );
+    assert_eq!(6, normalized.width());
+    assert_eq!(3, normalized.height());
+
+    // the decorative box can not be solved, but the pushes map back to the original board
+    let solution = Solver::new().solve(&Map::from(normalized)).unwrap();
+    let mapped = mapping.inverse().map_solution(&solution).unwrap();
+    let mut map = load(DECORATED);
+    for m in mapped.moves() {
+        assert!(map.possible_moves().contains(m));
+        map.apply_move(*m);
+    }
+    assert!(map.is_box(Pos::new(7, 3)) && map.is_destination(Pos::new(7, 3)));
+}